@@ -15,13 +15,12 @@ fn main() {
     // new I2C instance with rppal
     let i2c = I2c::new().unwrap();
 
-    // create a new driver with a default configuration
-    let mut msa301 = MSA301::new(i2c, 
-                                
-                                AccelConfig{
-                                    datarate: DataRate::_125Hz,
-                                    bandwidth: BandWidth::_62_5Hz,
-                                    ..Default::default()},
+    // create a new driver with a custom configuration
+    let mut msa301 = MSA301::new_with_configuration(i2c, 
+                                AccelConfig::builder()
+                                    .datarate(DataRate::_125Hz)
+                                    .build()
+                                    .unwrap(),
                                 ).unwrap();
          
     //msa301.init().unwrap();
@@ -101,12 +101,12 @@ fn main() -> ! {
     );
 
     // initialize MSA301 driver with a desired configuration
-    let mut msa301 = MSA301::new(i2c, 
-                        AccelConfig { 
-                            range: Range::_2g,
-                            datarate: DataRate::_3_90Hz,
-                            bandwidth: BandWidth::_1_95Hz,
-                            ..Default::default() }).unwrap();
+    let mut msa301 = MSA301::new_with_configuration(i2c, 
+                        AccelConfig::builder()
+                            .range(Range::_2g)
+                            .datarate(DataRate::_3_90Hz)
+                            .build()
+                            .unwrap()).unwrap();
     
     msa301.pin_config(IntPin::PushPull, IntActive::High).unwrap();
     
@@ -114,7 +114,7 @@ fn main() -> ! {
 
     //msa301.new_data_int(Flag::Enable).unwrap();
     
-    msa301.set_int_latch(true, IntLatch::NonLatched).unwrap();
+    msa301.config_int_latch(IntLatch::NonLatched).unwrap();
 
     //msa301.single_tap_int(Flag::Enable).unwrap();
 
@@ -0,0 +1,355 @@
+//! Async driver built on embedded-hal-async (enabled with the `async` feature)
+//!
+//! Covers construction, configuration, reading the measurements and the interrupts' status.
+//! The register encoding and decoding, and the shadow copy of the writable registers, are shared with the blocking driver.
+
+use super::*;
+use config::{axis_polarity_bits, polarity, xy_swap_bits, ConfigError, ConfigMismatch};
+use embedded_hal_1::i2c::ErrorKind;
+use embedded_hal_async::delay::DelayNs;
+use interface::{AsyncInterface, I2cAsyncInterface};
+use interrupt::{InterruptStatus, TapActiveStatus};
+use register::RegisterCache;
+use sensor::{mg_from_raw, raw_from_registers, QuantizedSample, Snapshot};
+#[cfg(feature = "float")]
+use sensor::{accel_from_raw, Acceleration};
+
+/// Holds the async driver instance with the interface, configuration struct and a shadow copy of the writable registers
+#[derive(Debug)]
+pub struct MSA301Async<T> {
+    /// The concrete async interface implementation (I2C by default)
+    interface: T,
+    config: AccelConfig,
+    registers: RegisterCache,
+}
+
+impl<I2C> MSA301Async<I2cAsyncInterface<I2C>>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Create a new instance of the async MSA301 driver using the I2C interface and the default configuration.
+    ///
+    /// ```rust,ignore
+    /// let mut msa301 = MSA301Async::new(i2c).await.unwrap();
+    /// let (x, y, z) = msa301.read_accel().await.unwrap();
+    /// ```
+    ///
+    /// Returns `Error::WrongDevice` if the PART_ID doesn't match.
+    pub async fn new(i2c: I2C) -> Result<Self, Error<ErrorKind>> {
+        Self::new_with_configuration(i2c, AccelConfig::default()).await
+    }
+
+    /// Create a new instance of the async MSA301 driver using the I2C interface and a user-specified configuration.
+    ///
+    /// Returns `Error::InvalidConfig` if the settings can't be used together
+    /// and `Error::WrongDevice` if the PART_ID doesn't match.
+    pub async fn new_with_configuration(i2c: I2C, config: AccelConfig) -> Result<Self, Error<ErrorKind>> {
+        Self::with_interface(I2cAsyncInterface::init(i2c, I2cAddress::Address), config).await
+    }
+
+    /// Create a new instance of the async MSA301 driver using the I2C interface, issuing a soft reset first.
+    pub async fn new_with_reset<D: DelayNs>(i2c: I2C, config: AccelConfig, delay: &mut D) -> Result<Self, Error<ErrorKind>> {
+        let mut msa = Self::create(I2cAsyncInterface::init(i2c, I2cAddress::Address), config)?;
+        msa.soft_reset(delay).await?;
+        Ok(msa)
+    }
+
+    /// Destroy driver instance, return I2C bus instance.
+    pub fn destroy(self) -> I2C {
+        self.interface.release()
+    }
+}
+
+impl<T, E> MSA301Async<T>
+where
+    T: AsyncInterface<Error = E>,
+{
+    /// Create a new instance of the async MSA301 driver using any interface implementing the `AsyncInterface` trait.
+    ///
+    /// The bandwidth is set to half of the datarate in normal mode.
+    /// Returns `Error::InvalidConfig` if the settings can't be used together
+    /// and `Error::WrongDevice` if the PART_ID doesn't match.
+    pub async fn with_interface(interface: T, config: AccelConfig) -> Result<Self, Error<E>> {
+        let mut msa = Self::create(interface, config)?;
+        msa.check_device_id().await?;
+        msa.sync_from_device().await?;
+        msa.init().await?;
+        Ok(msa)
+    }
+
+    /// Driver instance with a validated configuration, nothing is written to the sensor yet
+    fn create(interface: T, config: AccelConfig) -> Result<Self, ConfigError> {
+        Ok(MSA301Async {
+            interface,
+            config: config.validated()?,
+            registers: RegisterCache::default(),
+        })
+    }
+
+    /// Soft reset the sensor, wait until it responds with the correct PART_ID
+    /// and write the current configuration again.
+    /// The PART_ID is polled every 1 ms for up to 100 ms, starting 1 ms after the reset.
+    pub async fn soft_reset<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.write_register(Registers::SOFT_RESET, Bitmasks::SOFT_RESET).await?;
+        self.wait_for_device(delay).await?;
+        self.sync_from_device().await?;
+        self.init().await
+    }
+
+    /// Read all the writable registers (RES_RANGE to OFFSET_Z) in one transfer into the shadow copy
+    /// kept by the driver. Call it if the sensor may have been reconfigured without this driver.
+    pub async fn sync_from_device(&mut self) -> Result<(), Error<E>> {
+        self.interface
+            .read(RegisterCache::START.addr(), self.registers.buffer_mut())
+            .await
+            .map_err(Error::I2C)
+    }
+
+    /// Write all the writable registers from the shadow copy to the sensor,
+    /// e.g. to restore the settings after the sensor lost power.
+    pub async fn flush(&mut self) -> Result<(), Error<E>> {
+        for register in RegisterCache::WRITABLE {
+            if let Some(data) = self.registers.get(register) {
+                self.interface.write(register.addr(), data).await.map_err(Error::I2C)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the PART_ID is correct
+    async fn check_device_id(&mut self) -> Result<(), Error<E>> {
+        check_part_id(self.get_device_id().await?)
+    }
+
+    /// Poll the PART_ID until the sensor responds with the correct value,
+    /// ignoring the errors while it's resetting and waiting before each poll
+    /// (the PART_ID is constant, so the first poll must not run right after the reset)
+    async fn wait_for_device<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        let mut attempt = 1;
+        loop {
+            delay.delay_ms(RESET_POLL_INTERVAL_MS.into()).await;
+            if let Some(result) = reset_poll_outcome(self.get_device_id().await, attempt) {
+                return result;
+            }
+            attempt += 1;
+        }
+    }
+
+    async fn init(&mut self) -> Result<(), Error<E>> {
+        self.write_register(Registers::CFG_ODR, self.config.cfg_odr()).await?;
+        self.write_register(Registers::PWR_BW, self.config.pwr_bw()).await?;
+        self.write_register(Registers::RES_RANGE, self.config.res_range()).await?;
+        Ok(())
+    }
+
+    /// Get the configuration kept by the driver (see `read_config_from_device()` for the sensor's actual settings)
+    pub fn get_config(&self) -> AccelConfig {
+        self.config
+    }
+
+    /// Read the configuration from the sensor's CFG_ODR, PWR_BW and RES_RANGE registers
+    pub async fn read_config_from_device(&mut self) -> Result<AccelConfig, Error<E>> {
+        let [res_range, cfg_odr, pwr_bw] = self.read_config_registers().await?;
+        Ok(AccelConfig::from_registers(cfg_odr, pwr_bw, res_range))
+    }
+
+    /// Compare the configuration kept by the driver with the sensor's, e.g. to detect a brown-out reset
+    pub async fn verify_config(&mut self) -> Result<ConfigMismatch, Error<E>> {
+        let [res_range, cfg_odr, pwr_bw] = self.read_config_registers().await?;
+        Ok(ConfigMismatch::compare(&self.config, cfg_odr, pwr_bw, res_range))
+    }
+
+    /// Read the RES_RANGE, CFG_ODR and PWR_BW registers in one transfer
+    async fn read_config_registers(&mut self) -> Result<[u8; 3], Error<E>> {
+        let mut data = [0_u8; 3];
+        self.interface
+            .read(Registers::RES_RANGE.addr(), &mut data)
+            .await
+            .map_err(Error::I2C)?;
+        Ok(data)
+    }
+
+    /// Destroy driver instance, return interface instance.
+    pub fn release(self) -> T {
+        self.interface
+    }
+
+    /// Write to a register, skipped if the shadow copy shows it already has this value
+    async fn write_register(&mut self, address: Registers, data: u8) -> Result<(), Error<E>> {
+        if !self.registers.needs_write(address, data) {
+            return Ok(());
+        }
+        self.interface.write(address.addr(), data).await.map_err(Error::I2C)?;
+        self.registers.set(address, data);
+        Ok(())
+    }
+
+    /// Read from a register
+    async fn read_register(&mut self, address: Registers) -> Result<u8, Error<E>> {
+        let mut data: [u8; 1] = [0];
+        self.interface
+            .read(address.addr(), &mut data)
+            .await
+            .map_err(Error::I2C)
+            .and(Ok(data[0]))
+    }
+
+    /// Replace the bits selected by the bitmask with the new value,
+    /// starting from the shadow copy (the register is read only if it's not cached)
+    async fn modify_register(&mut self, address: Registers, bitmask: u8, value: u8) -> Result<(), Error<E>> {
+        let data = match self.registers.get(address) {
+            Some(data) => data,
+            None => self.read_register(address).await?,
+        };
+        self.write_register(address, (data & !bitmask) | value).await
+    }
+
+    /// Read the device ID ("who am I"). Returns decimal value 19.
+    pub async fn get_device_id(&mut self) -> Result<u8, Error<E>> {
+        self.read_register(Registers::PART_ID).await
+    }
+
+    /// Set output data rate (in normal mode the bandwidth is set to half of it)
+    ///
+    /// Returns `Error::InvalidConfig` if the datarate is not available in the current power mode.
+    pub async fn set_datarate(&mut self, odr: DataRate) -> Result<(), Error<E>> {
+        let config = self.config.with_datarate(odr)?;
+        self.apply_config(config).await
+    }
+
+    /// Set bandwidth (only in low power mode, in normal mode is always half of the datarate)
+    ///
+    /// Returns `Error::InvalidConfig` if the sensor is not in low power mode.
+    pub async fn set_bandwidth(&mut self, bandwidth: BandWidth) -> Result<(), Error<E>> {
+        let config = self.config.with_bandwidth(bandwidth)?;
+        self.apply_config(config).await
+    }
+
+    /// Set power mode (normal, suspend or low-power)
+    ///
+    /// Returns `Error::InvalidConfig` if the current datarate is not available in the new power mode.
+    pub async fn set_power_mode(&mut self, powermode: PowerMode) -> Result<(), Error<E>> {
+        let config = self.config.with_powermode(powermode)?;
+        self.apply_config(config).await
+    }
+
+    /// Set resolution in bits (14, 12, 10 or 8 bits)
+    pub async fn set_resolution(&mut self, resolution: Res) -> Result<(), Error<E>> {
+        let config = self.config.with_resolution(resolution)?;
+        self.apply_config(config).await
+    }
+
+    /// Set acceleration range (full scale +/-2g, +/-4g, +/-8g, +/-16g)
+    pub async fn set_range(&mut self, range: Range) -> Result<(), Error<E>> {
+        let config = self.config.with_range(range)?;
+        self.apply_config(config).await
+    }
+
+    /// Write the register bits that differ from the new configuration and keep it
+    async fn apply_config(&mut self, config: AccelConfig) -> Result<(), Error<E>> {
+        for (register, bitmask, value) in config.register_bits() {
+            self.modify_register(register, bitmask, value).await?;
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    /// Set the polarity of X/Y/Z axes (normal or reversed)
+    pub async fn set_axis_polarity(&mut self, x: Polarity, y: Polarity, z: Polarity) -> Result<(), Error<E>> {
+        self.modify_register(Registers::POLAR_SWP, Bitmasks::AXES_POLARITY, axis_polarity_bits(x, y, z)).await
+    }
+
+    /// Get the polarity of X/Y/Z axes
+    pub async fn get_axis_polarity(&mut self) -> Result<(Polarity, Polarity, Polarity), Error<E>> {
+        let data = self.read_register(Registers::POLAR_SWP).await?;
+        Ok((
+            polarity(data, Bitmasks::X_POLARITY),
+            polarity(data, Bitmasks::Y_POLARITY),
+            polarity(data, Bitmasks::Z_POLARITY),
+        ))
+    }
+
+    /// Swap X and Y axes outputs (Reversed) or keep them as they are (Normal)
+    pub async fn set_xy_swap(&mut self, swap: Polarity) -> Result<(), Error<E>> {
+        self.modify_register(Registers::POLAR_SWP, Bitmasks::XY_SWAP, xy_swap_bits(swap)).await
+    }
+
+    /// Get the X/Y axes swap setting
+    pub async fn get_xy_swap(&mut self) -> Result<Polarity, Error<E>> {
+        let data = self.read_register(Registers::POLAR_SWP).await?;
+        Ok(polarity(data, Bitmasks::XY_SWAP))
+    }
+
+    /// Read the contents of the X/Y/Z axes registers
+    async fn read_accel_registers(&mut self) -> Result<[u8; 6], Error<E>> {
+        let mut data = [0_u8; 6];
+        self.interface
+            .read(Registers::XAXIS_L.addr(), &mut data)
+            .await
+            .map_err(Error::I2C)?;
+        Ok(data)
+    }
+
+    /// Read the raw sensor values as a tuple, aligned to the selected resolution
+    pub async fn read_accel_raw(&mut self) -> Result<(i16, i16, i16), Error<E>> {
+        let data = self.read_accel_registers().await?;
+        Ok(raw_from_registers(data, self.config.resolution))
+    }
+
+    /// Read the measurements and the motion, new data, tap/active and orientation status in a single transfer
+    pub async fn snapshot(&mut self) -> Result<Snapshot, Error<E>> {
+        let mut data = [0_u8; Snapshot::LEN];
+        self.interface
+            .read(Registers::XAXIS_L.addr(), &mut data)
+            .await
+            .map_err(Error::I2C)?;
+        Ok(Snapshot::from_registers(data, self.config.range, self.config.resolution))
+    }
+
+    /// Read the accelerometer data quantized to 8 bits per axis, with the range code
+    /// (see `QuantizedSample` for the error bounds)
+    pub async fn read_accel_quantized(&mut self) -> Result<QuantizedSample, Error<E>> {
+        let data = self.read_accel_registers().await?;
+        Ok(QuantizedSample::from_registers(data, self.config.range))
+    }
+
+    /// Read the accelerometer data in mg as a tuple, using only integer math
+    /// (rounded to the nearest mg, correctly scaled according to the selected range and resolution).
+    pub async fn read_accel_mg(&mut self) -> Result<(i32, i32, i32), Error<E>> {
+        let raw = self.read_accel_raw().await?;
+        Ok(mg_from_raw(raw, self.config.range, self.config.resolution))
+    }
+
+    /// Read the accelerometer data as a tuple,
+    /// correctly scaled according to the selected range and resolution.
+    #[cfg(feature = "float")]
+    pub async fn read_accel(&mut self) -> Result<(f32, f32, f32), Error<E>> {
+        let raw = self.read_accel_raw().await?;
+        Ok(accel_from_raw(raw, self.config.range, self.config.resolution))
+    }
+
+    /// Read the accelerometer data as an `Acceleration` in g,
+    /// correctly scaled according to the selected range and resolution.
+    #[cfg(feature = "float")]
+    pub async fn read_acceleration(&mut self) -> Result<Acceleration, Error<E>> {
+        Ok(self.read_accel().await?.into())
+    }
+
+    /// Get motion interrupts status
+    pub async fn motion_int_status(&mut self) -> Result<InterruptStatus, Error<E>> {
+        let data = self.read_register(Registers::MOTION_INT).await?;
+        Ok(InterruptStatus::from_register(data))
+    }
+
+    /// Get the axis and sign of the first tap and active interrupt triggering
+    pub async fn tap_active_status(&mut self) -> Result<TapActiveStatus, Error<E>> {
+        let data = self.read_register(Registers::TAP_ACTIVE).await?;
+        Ok(TapActiveStatus::from_register(data))
+    }
+
+    /// Check if new data available
+    pub async fn is_new_data(&mut self) -> Result<bool, Error<E>> {
+        let data = self.read_register(Registers::DATA_INT).await?;
+        Ok(data & Bitmasks::NEW_DATA_INT != 0)
+    }
+}
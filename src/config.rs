@@ -2,7 +2,10 @@
 //!
 //! TO DO: 
 //! * check if all the functions are implemented
-//! * how to limit datarate/bandwidth settings for the normal and low power mode?//! 
+//! 
+//! The configuration is validated before it's written to the sensor: 1 Hz and 1.95 Hz datarates
+//! are not available in normal mode, 500 Hz and 1000 Hz are not available in low power mode.
+//! In normal mode the bandwidth is always half of the datarate and is set accordingly.
 //! 
 
 use super::*;
@@ -18,7 +21,8 @@ use super::*;
 // - resolution 14 bit  (default resolution)
 
 /// Accelerometer settings to configure the sensor
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AccelConfig {
     /// Axes X,Y,Z enabled
     pub enable_axes: (bool, bool, bool),
@@ -47,7 +51,164 @@ impl Default for AccelConfig {
     }
 }
 
+/// Settings that can't be used together
+#[derive(Debug, Clone, Copy)]
+pub enum ConfigError {
+    /// The datarate is not available in the power mode
+    /// (1 Hz and 1.95 Hz in normal mode, 500 Hz and 1000 Hz in low power mode)
+    DataRateNotAvailable {
+        /// Selected datarate
+        datarate: DataRate,
+        /// Selected power mode
+        powermode: PowerMode,
+    },
+    /// The bandwidth can only be set in low power mode
+    /// (in normal mode it is always half of the datarate)
+    BandwidthNotAvailable {
+        /// Current power mode
+        powermode: PowerMode,
+    },
+    /// The blocking driver always starts in normal mode
+    /// (use `into_low_power()` or `into_suspend()` after creating it)
+    PowerModeNotAvailable {
+        /// Selected power mode
+        powermode: PowerMode,
+    },
+}
+
+/// Settings that differ between the configuration kept by the driver and the sensor (`true` if different)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConfigMismatch {
+    /// Axes X,Y,Z enabled
+    pub enable_axes: bool,
+    /// Power mode
+    pub powermode: bool,
+    /// Output datarate
+    pub datarate: bool,
+    /// Bandwidth
+    pub bandwidth: bool,
+    /// Resolution
+    pub resolution: bool,
+    /// Full scale range
+    pub range: bool,
+}
+
+impl ConfigMismatch {
+    /// Compare the configuration kept by the driver with the CFG_ODR, PWR_BW and RES_RANGE register values
+    /// read from the sensor
+    pub(crate) fn compare(cached: &AccelConfig, cfg_odr: u8, pwr_bw: u8, res_range: u8) -> Self {
+        ConfigMismatch {
+            enable_axes: cached.enable_axes != enabled_axes(cfg_odr),
+            powermode: PowerMode::from(pwr_bw) != cached.powermode,
+            datarate: DataRate::from(cfg_odr) != cached.datarate,
+            bandwidth: BandWidth::from(pwr_bw) != cached.bandwidth,
+            resolution: Res::from(res_range) != cached.resolution,
+            range: Range::from(res_range) != cached.range,
+        }
+    }
+
+    /// Check if any of the settings differs
+    pub fn any(&self) -> bool {
+        self.enable_axes || self.powermode || self.datarate || self.bandwidth || self.resolution || self.range
+    }
+}
+
 impl AccelConfig {
+    /// Start from the default configuration and change only the settings you need
+    ///
+    /// ```rust,ignore
+    /// let config = AccelConfig::builder()
+    ///     .range(Range::_2g)
+    ///     .datarate(DataRate::_125Hz)
+    ///     .build()
+    ///     .unwrap();
+    /// let mut msa301 = MSA301::new_with_configuration(i2c, config).unwrap();
+    /// ```
+    pub fn builder() -> AccelConfigBuilder {
+        AccelConfigBuilder {
+            config: AccelConfig::default(),
+        }
+    }
+
+    /// Check that the settings can be used together
+    ///
+    /// ```rust,ignore
+    /// let config = AccelConfig { powermode: PowerMode::LowPower, datarate: DataRate::_1000Hz, ..Default::default() };
+    /// assert!(config.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        use DataRate::*;
+        let available = match self.powermode {
+            PowerMode::Normal => !matches!(self.datarate, _1Hz | _1_95Hz),
+            PowerMode::LowPower => !matches!(self.datarate, _500Hz | _1000Hz),
+            PowerMode::Suspend => true,
+        };
+        if available {
+            Ok(())
+        } else {
+            Err(ConfigError::DataRateNotAvailable {
+                datarate: self.datarate,
+                powermode: self.powermode,
+            })
+        }
+    }
+
+    /// In normal mode the bandwidth is always half of the datarate
+    fn derive_bandwidth(&mut self) {
+        if self.powermode == PowerMode::Normal {
+            self.bandwidth = self.datarate.half_bandwidth();
+        }
+    }
+
+    /// Validate the settings and derive the bandwidth
+    pub(crate) fn validated(mut self) -> Result<Self, ConfigError> {
+        self.validate()?;
+        self.derive_bandwidth();
+        Ok(self)
+    }
+
+    /// Copy of the configuration with a new datarate
+    pub(crate) fn with_datarate(mut self, datarate: DataRate) -> Result<Self, ConfigError> {
+        self.datarate = datarate;
+        self.validated()
+    }
+
+    /// Copy of the configuration with a new bandwidth (only in low power mode)
+    pub(crate) fn with_bandwidth(mut self, bandwidth: BandWidth) -> Result<Self, ConfigError> {
+        if self.powermode != PowerMode::LowPower {
+            return Err(ConfigError::BandwidthNotAvailable { powermode: self.powermode });
+        }
+        self.bandwidth = bandwidth;
+        self.validated()
+    }
+
+    /// Copy of the configuration with a new power mode
+    pub(crate) fn with_powermode(mut self, powermode: PowerMode) -> Result<Self, ConfigError> {
+        self.powermode = powermode;
+        self.validated()
+    }
+
+    /// Copy of the configuration with a new resolution
+    pub(crate) fn with_resolution(mut self, resolution: Res) -> Result<Self, ConfigError> {
+        self.resolution = resolution;
+        self.validated()
+    }
+
+    /// Copy of the configuration with a new range
+    pub(crate) fn with_range(mut self, range: Range) -> Result<Self, ConfigError> {
+        self.range = range;
+        self.validated()
+    }
+
+    /// Register, bitmask and value of the bits holding the configuration
+    pub(crate) fn register_bits(&self) -> [(Registers, u8, u8); 3] {
+        [
+            (Registers::RES_RANGE, Bitmasks::RESOLUTION | Bitmasks::FS, self.res_range()),
+            (Registers::CFG_ODR, Bitmasks::AXES_DISABLE | Bitmasks::ODR_MASK, self.cfg_odr()),
+            (Registers::PWR_BW, Bitmasks::PWR_MASK | Bitmasks::BW_MASK, self.pwr_bw()),
+        ]
+    }
+
     // do I need this or it's enough to call various setting functions in the `init()` function?
     /// Values to be written to the RES_RANGE register
     pub (crate) fn res_range(&self) -> u8 {
@@ -63,13 +224,13 @@ impl AccelConfig {
         let mut data: u8 = 0b1110_0000; // if bits 7:5 are set, all axes are DISABLED
         let (x,y,z) = self.enable_axes;
         if x {
-            data = data & !0b1000_0000 // clear bit 7 to enable axis X
+            data &= !0b1000_0000 // clear bit 7 to enable axis X
         }
         if y {
-            data = data & !0b0100_0000 // clear bit 6 to enable axis Y
+            data &= !0b0100_0000 // clear bit 6 to enable axis Y
         }
         if z {
-            data = data & !0b0010_0000 // clear bit 5 to enable axis Z
+            data &= !0b0010_0000 // clear bit 5 to enable axis Z
         }
 
         data |= self.datarate.value();
@@ -85,68 +246,319 @@ impl AccelConfig {
         data |= self.powermode.value();
         data
     }
+
+    /// Decode the CFG_ODR, PWR_BW and RES_RANGE register values
+    pub(crate) fn from_registers(cfg_odr: u8, pwr_bw: u8, res_range: u8) -> Self {
+        AccelConfig {
+            enable_axes: enabled_axes(cfg_odr),
+            powermode: PowerMode::from(pwr_bw),
+            datarate: DataRate::from(cfg_odr),
+            bandwidth: BandWidth::from(pwr_bw),
+            resolution: Res::from(res_range),
+            range: Range::from(res_range),
+        }
+    }
+}
+
+
+/// Builder for `AccelConfig`, created with `AccelConfig::builder()`
+#[derive(Debug, Clone, Copy)]
+pub struct AccelConfigBuilder {
+    config: AccelConfig,
 }
 
+impl AccelConfigBuilder {
+    /// Set full scale range
+    pub fn range(mut self, range: Range) -> Self {
+        self.config.range = range;
+        self
+    }
+
+    /// Set resolution in bits
+    pub fn resolution(mut self, resolution: Res) -> Self {
+        self.config.resolution = resolution;
+        self
+    }
+
+    /// Set output datarate
+    pub fn datarate(mut self, datarate: DataRate) -> Self {
+        self.config.datarate = datarate;
+        self
+    }
+
+    /// Set bandwidth (only used in low power mode, in normal mode is always half of the datarate)
+    pub fn bandwidth(mut self, bandwidth: BandWidth) -> Self {
+        self.config.bandwidth = bandwidth;
+        self
+    }
+
+    /// Set power mode (only used by the async driver, the blocking driver must be created in normal mode)
+    pub fn powermode(mut self, powermode: PowerMode) -> Self {
+        self.config.powermode = powermode;
+        self
+    }
+
+    /// Enable or disable X,Y,Z axes
+    pub fn axes(mut self, x: bool, y: bool, z: bool) -> Self {
+        self.config.enable_axes = (x, y, z);
+        self
+    }
+
+    /// Validate the settings and return the configuration
+    pub fn build(self) -> Result<AccelConfig, ConfigError> {
+        self.config.validated()
+    }
+}
 
 // === ARE THESE FUNCTIONS NECESSARY? === 
 
-impl<I2C, E> MSA301<I2C>
+impl<T, E, MODE> MSA301<T, MODE>
 where
-    I2C: Write<Error = E> + WriteRead<Error = E>,
+    T: Interface<Error = E>,
 {
-    /// Set output data rate
-    pub fn set_datarate(&mut self, odr: DataRate) -> Result<(), Error<E>> {
-        let reg = self.read_register(Registers::CFG_ODR)?;
-        let mut data = reg & !Bitmasks::ODR_MASK;
-        data |= odr.value();
-        self.write_register(Registers::CFG_ODR, data)?;
-        self.config.datarate = odr;
-        Ok(())
+    /// Read the configuration from the sensor's CFG_ODR, PWR_BW and RES_RANGE registers
+    pub fn read_config_from_device(&mut self) -> Result<AccelConfig, Error<E>> {
+        let [res_range, cfg_odr, pwr_bw] = self.read_config_registers()?;
+        Ok(AccelConfig::from_registers(cfg_odr, pwr_bw, res_range))
     }
 
-    /// Set bandwidth (in normal mode is always half of the datarate)
-    pub fn set_bandwidth(&mut self, bandwidth: BandWidth) -> Result<(), Error<E>> {
-        let reg = self.read_register(Registers::PWR_BW)?;     
-        let mut data = reg & !Bitmasks::BW_MASK;
-        data |= bandwidth.value();      
-        self.write_register(Registers::PWR_BW, data)?;      
-        self.config.bandwidth = bandwidth;
-        Ok(())
+    /// Compare the configuration kept by the driver with the sensor's, e.g. to detect a brown-out reset
+    ///
+    /// ```rust,ignore
+    /// if msa301.verify_config().unwrap().any() {
+    ///     msa301.flush().unwrap(); // write the settings again
+    /// }
+    /// ```
+    pub fn verify_config(&mut self) -> Result<ConfigMismatch, Error<E>> {
+        let [res_range, cfg_odr, pwr_bw] = self.read_config_registers()?;
+        Ok(ConfigMismatch::compare(&self.config, cfg_odr, pwr_bw, res_range))
     }
 
-    /// Set power mode (normal, suspend or low-power)
-    pub fn set_power_mode(&mut self, powermode: PowerMode) -> Result<(), Error<E>> {
-        let reg = self.read_register(Registers::PWR_BW)?;     
-        let mut data = reg & !Bitmasks::PWR_MASK;
-        data |= powermode.value();      
-        self.write_register(Registers::PWR_BW, data)?;      
-        self.config.powermode = powermode;
-        Ok(())
+    /// Read the RES_RANGE, CFG_ODR and PWR_BW registers in one transfer
+    fn read_config_registers(&mut self) -> Result<[u8; 3], Error<E>> {
+        let mut data = [0_u8; 3];
+        self.interface
+            .read(Registers::RES_RANGE.addr(), &mut data)
+            .map_err(Error::I2C)?;
+        Ok(data)
+    }
+
+    /// Set output data rate (in normal mode the bandwidth is set to half of it)
+    /// 
+    /// Returns `Error::InvalidConfig` if the datarate is not available in the current power mode.
+    pub fn set_datarate(&mut self, odr: DataRate) -> Result<(), Error<E>> {
+        let config = self.config.with_datarate(odr)?;
+        self.apply_config(config)
     }
 
     /// Set resolution in bits (14, 12, 10 or 8 bits)
     pub fn set_resolution(&mut self, resolution: Res) -> Result<(), Error<E>> {
-        let reg = self.read_register(Registers::RES_RANGE)?;     
-        let mut data = reg & !Bitmasks::RESOLUTION;
-        data |= resolution.value();      
-        self.write_register(Registers::RES_RANGE, data)?;   
-        self.config.resolution = resolution;   
-        Ok(())
+        let config = self.config.with_resolution(resolution)?;
+        self.apply_config(config)
     }
 
     /// Set acceleration range (full scale +/-2g, +/-4g, +/-8g, +/-16g)
     pub fn set_range(&mut self, range: Range) -> Result<(), Error<E>> {
-        let reg = self.read_register(Registers::RES_RANGE)?;     
-        let mut data = reg & !Bitmasks::FS;
-        data |= range.value();      
-        self.write_register(Registers::RES_RANGE, data)?;      
-        self.config.range = range;
+        let config = self.config.with_range(range)?;
+        self.apply_config(config)
+    }
+
+    /// Write the register bits that differ from the new configuration and keep it
+    pub(crate) fn apply_config(&mut self, config: AccelConfig) -> Result<(), Error<E>> {
+        for (register, bitmask, value) in config.register_bits() {
+            self.modify_register(register, bitmask, value)?;
+        }
+        self.config = config;
         Ok(())
     }
    
-    /// Get scaling factor (FOR TEST ONLY)
+    /// Set the polarity of X/Y/Z axes (normal or reversed)
+    ///
+    /// ```rust,ignore
+    /// msa301.set_axis_polarity(Polarity::Reversed, Polarity::Normal, Polarity::Normal).unwrap();
+    /// ```
+    ///
+    pub fn set_axis_polarity(&mut self, x: Polarity, y: Polarity, z: Polarity) -> Result<(), Error<E>> {
+        self.modify_register(Registers::POLAR_SWP, Bitmasks::AXES_POLARITY, axis_polarity_bits(x, y, z))
+    }
+
+    /// Get the polarity of X/Y/Z axes
+    pub fn get_axis_polarity(&mut self) -> Result<(Polarity, Polarity, Polarity), Error<E>> {
+        let data = self.read_register(Registers::POLAR_SWP)?;
+        Ok((
+            polarity(data, Bitmasks::X_POLARITY),
+            polarity(data, Bitmasks::Y_POLARITY),
+            polarity(data, Bitmasks::Z_POLARITY),
+        ))
+    }
+
+    /// Swap X and Y axes outputs (Reversed) or keep them as they are (Normal)
+    pub fn set_xy_swap(&mut self, swap: Polarity) -> Result<(), Error<E>> {
+        self.modify_register(Registers::POLAR_SWP, Bitmasks::XY_SWAP, xy_swap_bits(swap))
+    }
+
+    /// Get the X/Y axes swap setting
+    pub fn get_xy_swap(&mut self) -> Result<Polarity, Error<E>> {
+        let data = self.read_register(Registers::POLAR_SWP)?;
+        Ok(polarity(data, Bitmasks::XY_SWAP))
+    }
+
+    /// Get scaling factor (LSB per g) for the selected range and resolution (FOR TEST ONLY)
+    #[cfg(feature = "float")]
     pub fn get_scale(&mut self) -> Result<f32, Error<E>> {
-        Ok(self.config.range.sensitivity())
+        Ok(sensor::sensitivity(self.config.range, self.config.resolution))
     }
 
 }
+
+/// Axes X,Y,Z enabled in the CFG_ODR register value (bit cleared means enabled)
+fn enabled_axes(cfg_odr: u8) -> (bool, bool, bool) {
+    (cfg_odr & 0b1000_0000 == 0, cfg_odr & 0b0100_0000 == 0, cfg_odr & 0b0010_0000 == 0)
+}
+
+/// Bits to be written to the POLAR_SWP register for the polarity of X/Y/Z axes
+pub(crate) fn axis_polarity_bits(x: Polarity, y: Polarity, z: Polarity) -> u8 {
+    let mut data: u8 = 0;
+    if x.status() {
+        data |= Bitmasks::X_POLARITY;
+    }
+    if y.status() {
+        data |= Bitmasks::Y_POLARITY;
+    }
+    if z.status() {
+        data |= Bitmasks::Z_POLARITY;
+    }
+    data
+}
+
+/// Bit to be written to the POLAR_SWP register for the X/Y axes swap
+pub(crate) fn xy_swap_bits(swap: Polarity) -> u8 {
+    match swap {
+        Polarity::Normal => 0,
+        Polarity::Reversed => Bitmasks::XY_SWAP,
+    }
+}
+
+/// Reversed if any of the bits in the bitmask is set
+pub(crate) fn polarity(data: u8, bitmask: u8) -> Polarity {
+    match data & bitmask {
+        0 => Polarity::Normal,
+        _ => Polarity::Reversed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(powermode: PowerMode, datarate: DataRate) -> AccelConfig {
+        AccelConfig { powermode, datarate, ..Default::default() }
+    }
+
+    #[test]
+    fn datarates_rejected_in_low_power_mode() {
+        for datarate in [DataRate::_500Hz, DataRate::_1000Hz] {
+            assert!(matches!(
+                config(PowerMode::LowPower, datarate).validate(),
+                Err(ConfigError::DataRateNotAvailable { powermode: PowerMode::LowPower, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn datarates_rejected_in_normal_mode() {
+        for datarate in [DataRate::_1Hz, DataRate::_1_95Hz] {
+            assert!(matches!(
+                config(PowerMode::Normal, datarate).validate(),
+                Err(ConfigError::DataRateNotAvailable { powermode: PowerMode::Normal, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn bandwidth_rejected_outside_low_power_mode() {
+        for powermode in [PowerMode::Normal, PowerMode::Suspend] {
+            assert!(matches!(
+                config(powermode, DataRate::_125Hz).with_bandwidth(BandWidth::_31_25Hz),
+                Err(ConfigError::BandwidthNotAvailable { .. })
+            ));
+        }
+        let config = config(PowerMode::LowPower, DataRate::_125Hz).with_bandwidth(BandWidth::_31_25Hz).unwrap();
+        assert_eq!(config.bandwidth, BandWidth::_31_25Hz);
+    }
+
+    #[test]
+    fn valid_config_in_each_mode() {
+        assert!(config(PowerMode::Normal, DataRate::_1000Hz).validate().is_ok());
+        assert!(config(PowerMode::LowPower, DataRate::_1Hz).validate().is_ok());
+        assert!(config(PowerMode::LowPower, DataRate::_250Hz).validate().is_ok());
+        assert!(config(PowerMode::Suspend, DataRate::_1000Hz).validate().is_ok());
+        assert!(config(PowerMode::Suspend, DataRate::_1Hz).validate().is_ok());
+    }
+
+    #[test]
+    fn power_on_registers_mismatch() {
+        // power-on values: RES_RANGE = 0x00, CFG_ODR = 0x0F, PWR_BW = 0xDE
+        let decoded = AccelConfig::from_registers(0x0F, 0xDE, 0x00);
+        assert_eq!(decoded.datarate, DataRate::_1000Hz);
+        assert_eq!(decoded.bandwidth, BandWidth::_500Hz);
+        assert_eq!(decoded.powermode, PowerMode::Suspend);
+        assert_eq!(decoded.enable_axes, (true, true, true));
+        let mismatch = ConfigMismatch::compare(&AccelConfig::default(), 0x0F, 0xDE, 0x00);
+        assert!(mismatch.any());
+        assert!(!mismatch.enable_axes && !mismatch.resolution);
+        assert!(mismatch.powermode && mismatch.datarate && mismatch.bandwidth && mismatch.range);
+    }
+
+    #[test]
+    fn written_registers_match() {
+        let config = AccelConfig::builder().axes(true, false, true).build().unwrap();
+        let mismatch = ConfigMismatch::compare(&config, config.cfg_odr(), config.pwr_bw(), config.res_range());
+        assert!(!mismatch.any());
+        // reserved bits are ignored
+        let mismatch = ConfigMismatch::compare(&config, config.cfg_odr() | 0x10, config.pwr_bw() | 0x21, config.res_range() | 0xF0);
+        assert!(!mismatch.any());
+        let mismatch = ConfigMismatch::compare(&config, config.cfg_odr() & !0x40, config.pwr_bw(), config.res_range());
+        assert!(mismatch.enable_axes && !mismatch.datarate);
+    }
+
+    #[test]
+    fn bandwidth_is_half_the_datarate_in_normal_mode() {
+        let config = AccelConfig::builder().datarate(DataRate::_125Hz).build().unwrap();
+        assert_eq!(config.bandwidth, BandWidth::_62_5Hz);
+        let config = AccelConfig::builder()
+            .powermode(PowerMode::LowPower)
+            .datarate(DataRate::_125Hz)
+            .bandwidth(BandWidth::_7_81Hz)
+            .build()
+            .unwrap();
+        assert_eq!(config.bandwidth, BandWidth::_7_81Hz);
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn config_round_trip() {
+        let config = AccelConfig {
+            datarate: DataRate::_62_5Hz,
+            bandwidth: BandWidth::_31_25Hz,
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            json,
+            r#"{"enable_axes":[true,true,true],"powermode":"Normal","datarate":"62.5Hz","bandwidth":"31.25Hz","resolution":"14bit","range":"4g"}"#
+        );
+
+        let decoded: AccelConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.enable_axes, config.enable_axes);
+        assert_eq!(decoded.powermode, config.powermode);
+        assert_eq!(decoded.datarate, config.datarate);
+        assert_eq!(decoded.bandwidth, config.bandwidth);
+        assert_eq!(decoded.resolution, config.resolution);
+        assert_eq!(decoded.range, config.range);
+    }
+}
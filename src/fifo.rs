@@ -3,8 +3,6 @@
 //! - check if all FIFO-related functions are implemented
 //! - is a FIFO config struct necessary?
 
-use super::*;
-
 /*
 
 /// FIFO settings
@@ -2,14 +2,7 @@
 use super::Interface;
 use embedded_hal::blocking::i2c::{Write, WriteRead};
 
-/// Errors in this crate
-#[derive(Debug)]
-pub enum Error<CommE> {
-    /// Communication error
-    Comm(CommE),
-}
-
-/// Pressure sensor address for I2C communication
+/// Accelerometer address for I2C communication
 #[allow(non_camel_case_types)]
 pub enum I2cAddress {    
     /// Only one I2C address
@@ -23,6 +16,7 @@ impl I2cAddress {
 }
 
 /// This holds `I2C` and device address
+#[derive(Debug, Default)]
 pub struct I2cInterface<I2C> {
     i2c: I2C,
     dev_addr: u8,    
@@ -32,13 +26,18 @@ impl<I2C> I2cInterface<I2C> {
     /// Initializes an Interface with `I2C` instance and device address
     /// # Arguments
     /// * `i2c` - I2C instance
-    /// * `dev_addr` - `I2cAddress`: register address for the pressure sensor
+    /// * `dev_addr` - `I2cAddress`: register address for the accelerometer
     pub fn init(i2c: I2C, dev_addr: I2cAddress) -> Self {
         Self {
             i2c,
             dev_addr: dev_addr.addr(),            
         }
     }
+
+    /// Destroy the interface, return `I2C` instance
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
 }
 
 /// Implementation of `Interface`
@@ -46,25 +45,13 @@ impl<I2C, CommE> Interface for I2cInterface<I2C>
 where
     I2C: WriteRead<Error = CommE> + Write<Error = CommE>,
 {
-    type Error = Error<CommE>;
+    type Error = CommE;
 
     fn write(&mut self, addr: u8, value: u8) -> Result<(), Self::Error> {
-        //let sensor_addr = self.dev_addr;        
-        core::prelude::v1::Ok(
-            self.i2c
-                //.write(sensor_addr, &[addr, value])
-                .write(self.dev_addr, &[addr, value])
-                .map_err(Error::Comm)?,
-        )
+        self.i2c.write(self.dev_addr, &[addr, value])
     }
 
     fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
-        //let sensor_addr = self.dev_addr;        
-        core::prelude::v1::Ok(
-            self.i2c
-                //.write_read(sensor_addr, &[addr], buffer)
-                .write_read(self.dev_addr, &[addr], buffer)
-                .map_err(Error::Comm)?,
-        )
+        self.i2c.write_read(self.dev_addr, &[addr], buffer)
     }
 }
@@ -0,0 +1,52 @@
+//! Async I2C Interface for embedded-hal-async
+use super::AsyncInterface;
+use super::i2c::I2cAddress;
+use embedded_hal_1::i2c::{Error, ErrorKind};
+use embedded_hal_async::i2c::I2c;
+
+/// This holds async `I2C` and device address
+#[derive(Debug, Default)]
+pub struct I2cAsyncInterface<I2C> {
+    i2c: I2C,
+    dev_addr: u8,
+}
+
+impl<I2C> I2cAsyncInterface<I2C> {
+    /// Initializes an Interface with `I2C` instance and device address
+    /// # Arguments
+    /// * `i2c` - I2C instance
+    /// * `dev_addr` - `I2cAddress`: register address for the accelerometer
+    pub fn init(i2c: I2C, dev_addr: I2cAddress) -> Self {
+        Self {
+            i2c,
+            dev_addr: dev_addr.addr(),
+        }
+    }
+
+    /// Destroy the interface, return `I2C` instance
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+/// Implementation of `AsyncInterface`, bus errors are mapped to their `ErrorKind`
+impl<I2C> AsyncInterface for I2cAsyncInterface<I2C>
+where
+    I2C: I2c,
+{
+    type Error = ErrorKind;
+
+    async fn write(&mut self, addr: u8, value: u8) -> Result<(), Self::Error> {
+        self.i2c
+            .write(self.dev_addr, &[addr, value])
+            .await
+            .map_err(|e| e.kind())
+    }
+
+    async fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.i2c
+            .write_read(self.dev_addr, &[addr], buffer)
+            .await
+            .map_err(|e| e.kind())
+    }
+}
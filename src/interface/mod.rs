@@ -1,6 +1,16 @@
 //! Interface trait
 pub mod i2c;
 pub use self::i2c::I2cInterface;
+pub mod mux;
+pub use self::mux::{MuxChannel, MuxInterface, Tca9548a};
+#[cfg(feature = "eh1")]
+pub mod i2c_eh1;
+#[cfg(feature = "eh1")]
+pub use self::i2c_eh1::I2cEh1Interface;
+#[cfg(feature = "async")]
+pub mod i2c_async;
+#[cfg(feature = "async")]
+pub use self::i2c_async::I2cAsyncInterface;
 
 /// Interface Trait. `I2cInterface` implements this.
 pub trait Interface {
@@ -15,4 +25,21 @@ pub trait Interface {
     /// * `addr` - register address
     /// * `buffer` - buffer to store read data
     fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error>;
-}
\ No newline at end of file
+}
+
+/// Async Interface Trait. `I2cAsyncInterface` implements this.
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait AsyncInterface {
+    type Error;
+    /// Writes a byte to a sensor's specified register address.
+    /// # Arguments    
+    /// * `addr` - register address
+    /// * `value` - value to write
+    async fn write(&mut self, addr: u8, value: u8) -> Result<(), Self::Error>;
+    /// Reads multiple bytes from a sensor's specified register address.
+    /// # Arguments    
+    /// * `addr` - register address
+    /// * `buffer` - buffer to store read data
+    async fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,108 @@
+//! I2C multiplexer Interface (TCA9548A and compatible), to use several MSA301 on one bus
+//!
+//! The multiplexer owns the bus, and every sensor gets its own `MuxInterface` borrowing it.
+//! The channel is selected before every transaction, so the drivers can be used in any order.
+//!
+//! ```rust,ignore
+//! let mux = Tca9548a::new(i2c, 0x70);
+//! let mut front = MSA301::with_interface(mux.channel(MuxChannel::Ch0), AccelConfig::default()).unwrap();
+//! let mut back = MSA301::with_interface(mux.channel(MuxChannel::Ch1), AccelConfig::default()).unwrap();
+//! let (x, y, z) = front.read_accel().unwrap();
+//! let (x, y, z) = back.read_accel().unwrap();
+//! ```
+use super::Interface;
+use super::i2c::I2cAddress;
+use core::cell::RefCell;
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+/// Multiplexer channel
+#[derive(Debug, Clone, Copy)]
+pub enum MuxChannel {
+    /// Channel 0
+    Ch0 = 0,
+    /// Channel 1
+    Ch1 = 1,
+    /// Channel 2
+    Ch2 = 2,
+    /// Channel 3
+    Ch3 = 3,
+    /// Channel 4
+    Ch4 = 4,
+    /// Channel 5
+    Ch5 = 5,
+    /// Channel 6
+    Ch6 = 6,
+    /// Channel 7
+    Ch7 = 7,
+}
+
+impl MuxChannel {
+    /// Value to be written to the multiplexer control register to enable only this channel
+    pub fn value(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+/// This holds the shared `I2C` bus and the multiplexer address
+#[derive(Debug)]
+pub struct Tca9548a<I2C> {
+    i2c: RefCell<I2C>,
+    mux_addr: u8,
+}
+
+impl<I2C> Tca9548a<I2C> {
+    /// Initializes the multiplexer with `I2C` instance and multiplexer address
+    /// # Arguments
+    /// * `i2c` - I2C instance
+    /// * `mux_addr` - multiplexer address (0x70 to 0x77 for TCA9548A, depending on A0-A2 pins)
+    pub fn new(i2c: I2C, mux_addr: u8) -> Self {
+        Self {
+            i2c: RefCell::new(i2c),
+            mux_addr,
+        }
+    }
+
+    /// Create an interface for the MSA301 connected to the selected channel
+    pub fn channel(&self, channel: MuxChannel) -> MuxInterface<'_, I2C> {
+        MuxInterface {
+            bus: &self.i2c,
+            mux_addr: self.mux_addr,
+            channel,
+            dev_addr: I2cAddress::Address.addr(),
+        }
+    }
+
+    /// Destroy the multiplexer, return `I2C` instance
+    pub fn release(self) -> I2C {
+        self.i2c.into_inner()
+    }
+}
+
+/// This holds a reference to the shared `I2C` bus, the multiplexer channel and the device address
+#[derive(Debug)]
+pub struct MuxInterface<'a, I2C> {
+    bus: &'a RefCell<I2C>,
+    mux_addr: u8,
+    channel: MuxChannel,
+    dev_addr: u8,
+}
+
+/// Implementation of `Interface`
+impl<I2C, CommE> Interface for MuxInterface<'_, I2C>
+where
+    I2C: WriteRead<Error = CommE> + Write<Error = CommE>,
+{
+    type Error = CommE;
+
+    fn write(&mut self, addr: u8, value: u8) -> Result<(), Self::Error> {
+        let mut i2c = self.bus.borrow_mut();
+        i2c.write(self.mux_addr, &[self.channel.value()])?;
+        i2c.write(self.dev_addr, &[addr, value])
+    }
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let mut i2c = self.bus.borrow_mut();
+        i2c.write(self.mux_addr, &[self.channel.value()])?;
+        i2c.write_read(self.dev_addr, &[addr], buffer)
+    }
+}
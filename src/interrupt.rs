@@ -8,7 +8,8 @@
 use super::*;
 
 /// Motion interrupts status
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InterruptStatus {
     pub orientation: bool,
     pub single_tap: bool,
@@ -17,8 +18,65 @@ pub struct InterruptStatus {
     pub freefall: bool,
 }
 
+impl InterruptStatus {
+    /// Decode the contents of the MOTION_INT register
+    pub(crate) fn from_register(data: u8) -> Self {
+        InterruptStatus {
+            orientation: data & Bitmasks::ORIENT_INT != 0,
+            single_tap: data & Bitmasks::S_TAP_INT != 0,
+            double_tap: data & Bitmasks::D_TAP_INT != 0,
+            active: data & Bitmasks::ACTIVE_INT != 0,
+            freefall: data & Bitmasks::FREEFALL_INT != 0,
+        }
+    }
+}
+
+/// Axis and sign of the first tap and active interrupt triggering
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TapActiveStatus {
+    pub tap_sign: Sign,
+    pub tap_first_axis: Option<Axis>,
+    pub active_sign: Sign,
+    pub active_first_axis: Option<Axis>,
+}
+
+impl TapActiveStatus {
+    /// Decode the contents of the TAP_ACTIVE register (sign bit cleared if positive, set if negative)
+    pub(crate) fn from_register(data: u8) -> Self {
+        TapActiveStatus {
+            tap_sign: sign(data, Bitmasks::TAP_SIGN),
+            tap_first_axis: first_axis(data, Bitmasks::TAP_FIRST_X, Bitmasks::TAP_FIRST_Y, Bitmasks::TAP_FIRST_Z),
+            active_sign: sign(data, Bitmasks::ACTIVE_SIGN),
+            active_first_axis: first_axis(data, Bitmasks::ACTIVE_FIRST_X, Bitmasks::ACTIVE_FIRST_Y, Bitmasks::ACTIVE_FIRST_Z),
+        }
+    }
+}
+
+/// Negative if any of the bits in the bitmask is set
+fn sign(data: u8, bitmask: u8) -> Sign {
+    match data & bitmask {
+        0 => Sign::Positive,
+        _ => Sign::Negative,
+    }
+}
+
+/// Axis whose bit is set, if any
+fn first_axis(data: u8, x: u8, y: u8, z: u8) -> Option<Axis> {
+    if data & x != 0 {
+        Some(Axis::X)
+    } else if data & y != 0 {
+        Some(Axis::Y)
+    } else if data & z != 0 {
+        Some(Axis::Z)
+    } else {
+        None
+    }
+}
+
 /// Motion interrupts status
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InterruptConfig {
     pub pin_output: IntPin,
     pub pin_active: IntActive,
@@ -45,13 +103,90 @@ impl Default for InterruptConfig {
     }    
 }
 
-impl<I2C, E> MSA301<I2C>
+/// Interrupts routed to the INT1 pin
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterruptMap {
+    pub orientation: Flag,
+    pub single_tap: Flag,
+    pub double_tap: Flag,
+    pub active: Flag,
+    pub freefall: Flag,
+    pub new_data: Flag,
+}
+
+impl Default for InterruptMap {
+    fn default() -> Self {
+        InterruptMap {
+            orientation: Flag::Disable,
+            single_tap: Flag::Disable,
+            double_tap: Flag::Disable,
+            active: Flag::Disable,
+            freefall: Flag::Disable,
+            new_data: Flag::Disable,
+        }
+    }
+}
+
+impl InterruptMap {
+    /// Value to be written to the INT_MAP0 register
+    pub(crate) fn int_map0(&self) -> u8 {
+        let mut data: u8 = 0;
+        if self.orientation.status() {
+            data |= Bitmasks::INT1_ORIENT;
+        }
+        if self.single_tap.status() {
+            data |= Bitmasks::INT1_S_TAP;
+        }
+        if self.double_tap.status() {
+            data |= Bitmasks::INT1_D_TAP;
+        }
+        if self.active.status() {
+            data |= Bitmasks::INT1_ACTIVE;
+        }
+        if self.freefall.status() {
+            data |= Bitmasks::INT1_FREEFALL;
+        }
+        data
+    }
+
+    /// Value to be written to the INT_MAP1 register
+    pub(crate) fn int_map1(&self) -> u8 {
+        let mut data: u8 = 0;
+        if self.new_data.status() {
+            data |= Bitmasks::INT1_NEW_DATA;
+        }
+        data
+    }
+
+    /// Decode the contents of the INT_MAP0 and INT_MAP1 registers
+    pub(crate) fn from_registers(int_map0: u8, int_map1: u8) -> Self {
+        InterruptMap {
+            orientation: flag(int_map0, Bitmasks::INT1_ORIENT),
+            single_tap: flag(int_map0, Bitmasks::INT1_S_TAP),
+            double_tap: flag(int_map0, Bitmasks::INT1_D_TAP),
+            active: flag(int_map0, Bitmasks::INT1_ACTIVE),
+            freefall: flag(int_map0, Bitmasks::INT1_FREEFALL),
+            new_data: flag(int_map1, Bitmasks::INT1_NEW_DATA),
+        }
+    }
+}
+
+/// Enabled if any of the bits in the bitmask is set
+fn flag(data: u8, bitmask: u8) -> Flag {
+    match data & bitmask {
+        0 => Flag::Disable,
+        _ => Flag::Enable,
+    }
+}
+
+impl<T, E, MODE> MSA301<T, MODE>
 where
-    I2C: Write<Error = E> + WriteRead<Error = E>,
+    T: Interface<Error = E>,
 {
     /// Enable/disable new data interrupt
     /// 
-    /// '''rust
+    /// ```rust,ignore
     /// msa301.new_data_int(Flag::Enable).unwrap();
     /// ```
     /// 
@@ -65,7 +200,7 @@ where
     
     /// Enable/disable freefall interrupt
     /// 
-    /// '''rust
+    /// ```rust,ignore
     /// msa301.freefall_int(Flag::Enable).unwrap();
     /// ```
     /// 
@@ -79,7 +214,7 @@ where
 
     /// Enable/disable orientation interrupt
     /// 
-    /// '''rust
+    /// ```rust,ignore
     /// msa301.orient_int(Flag::Enable).unwrap();
     /// ```
     /// 
@@ -93,7 +228,7 @@ where
 
     /// Enable/disable single tap interrupt
     ///  
-    /// '''rust
+    /// ```rust,ignore
     /// msa301.single_tap_int(Flag::Enable).unwrap();
     /// ```
     /// 
@@ -107,7 +242,7 @@ where
 
     /// Enable/disable double tap interrupt
     ///  
-    /// '''rust
+    /// ```rust,ignore
     /// msa301.double_tap_int(Flag::Enable).unwrap();
     /// ```
     /// 
@@ -121,7 +256,7 @@ where
 
     /// Enable/disable active interrupt for X axis
     ///  
-    /// '''rust
+    /// ```rust,ignore
     /// msa301.active_xaxis_int(Flag::Enable).unwrap();
     /// ```
     /// 
@@ -174,86 +309,82 @@ where
     }
 
     /// Configure interrupt latching
-    pub fn config_int_latch(&mut self, reset: bool, setting: IntLatch) -> Result<(), Error<E>> {
-        
-        // should RESET be separated? probably!
-
-        let reg = self.read_register(Registers::INT_LATCH)?;
-        
-        let mut data: u8 = 0;
-
-        data = match reset {
-            true => 0b1000_0000,
-            false => 0b0000_0000,
-        };
-
-        // let mut data = reg & !Bitmasks::LATCH_INT;
-
-        data |= setting.value();
+    ///
+    /// ```rust,ignore
+    /// msa301.config_int_latch(IntLatch::Latched).unwrap();
+    /// ```
+    ///
+    pub fn config_int_latch(&mut self, setting: IntLatch) -> Result<(), Error<E>> {
+        self.modify_register(Registers::INT_LATCH, Bitmasks::LATCH_INT | Bitmasks::RESET_INT, setting.value())
+    }
 
-        Ok(())
+    /// Get interrupt latching setting
+    pub fn get_int_latch(&mut self) -> Result<IntLatch, Error<E>> {
+        let data = self.read_register(Registers::INT_LATCH)?;
+        Ok(IntLatch::from_bits(data & Bitmasks::LATCH_INT))
+    }
 
+    /// Reset all latched interrupts, keeping the latching setting
+    ///
+    /// ```rust,ignore
+    /// let status = msa301.motion_int_status().unwrap();
+    /// msa301.reset_latched_interrupts().unwrap();
+    /// ```
+    ///
+    pub fn reset_latched_interrupts(&mut self) -> Result<(), Error<E>> {
+        // the reset bit clears itself, so it's written every time and never kept in the shadow copy
+        let reg = self.registers.get(Registers::INT_LATCH).unwrap_or_default();
+        let data = (reg & Bitmasks::LATCH_INT) | Bitmasks::RESET_INT;
+        self.interface.write(Registers::INT_LATCH.addr(), data).map_err(Error::I2C)
     }
 
     /// Get motion interrupts status
     pub fn motion_int_status(&mut self) -> Result<InterruptStatus, Error<E>> {
         let data = self.read_register(Registers::MOTION_INT)?;
-        let mask: u8 = 0b1111_1110;
-        let status = InterruptStatus {
-                        
-            orientation: match (data >> 6) &!mask {
-                1 => true,
-                _ => false,                
-            },
-            single_tap: match (data >> 5) &!mask  {
-                1 => true,
-                _ => false,
-            },
-            double_tap: match (data >> 4) &!mask  {
-                1 => true,
-                _ => false,
-            },
-            active: match (data >> 2) &!mask  {
-                1 => true,
-                _ => false,
-            },
-            freefall: match data &!mask  {
-                1 => true,
-                _ => false,
-            },
-        };
-        Ok(status)
+        Ok(InterruptStatus::from_register(data))
     }
 
 
         
 
+    /// Get the axis and sign of the first tap and active interrupt triggering
+    ///
+    /// ```rust,ignore
+    /// let status = msa301.tap_active_status().unwrap();
+    /// if let Some(Axis::Z) = status.tap_first_axis {
+    ///     println!("tap on top, {:?}", status.tap_sign);
+    /// }
+    /// ```
+    ///
+    pub fn tap_active_status(&mut self) -> Result<TapActiveStatus, Error<E>> {
+        let data = self.read_register(Registers::TAP_ACTIVE)?;
+        Ok(TapActiveStatus::from_register(data))
+    }
+
     /// Check if new data available
     pub fn is_new_data(&mut self) -> Result<bool, Error<E>> {
-        let data = self.read_register(Registers::DATA_INT)?;
-        match data & 0x1 {            
-            1 => Ok(true),
-            _ => Ok(false)            
-        }
+        self.is_register_bit_flag_high(Registers::DATA_INT, Bitmasks::NEW_DATA_INT)
     }
 
-        /*
-
-    /// TEST: route new data interrupt to pin INT
-    pub fn new_data_pin(&mut self, setting: Flag) -> Result<(), Error<E>> {
-                
-        let val = match setting {
-            Flag::Enable => 0x01,
-            Flag::Disable => 0x00,
-        };
-
-        self.write_register(Registers::INT_MAP1, val)?;
-
+    /// Route interrupts to the INT1 pin. The interrupts need to be enabled as well.
+    ///
+    /// ```rust,ignore
+    /// msa301.map_interrupts(InterruptMap { new_data: Flag::Enable, ..Default::default() }).unwrap();
+    /// msa301.new_data_int(Flag::Enable).unwrap();
+    /// ```
+    ///
+    pub fn map_interrupts(&mut self, map: InterruptMap) -> Result<(), Error<E>> {
+        self.write_register(Registers::INT_MAP0, map.int_map0())?;
+        self.write_register(Registers::INT_MAP1, map.int_map1())?;
         Ok(())
-
     }
 
-    */
+    /// Read which interrupts are routed to the INT1 pin
+    pub fn interrupt_map(&mut self) -> Result<InterruptMap, Error<E>> {
+        let int_map0 = self.read_register(Registers::INT_MAP0)?;
+        let int_map1 = self.read_register(Registers::INT_MAP1)?;
+        Ok(InterruptMap::from_registers(int_map0, int_map1))
+    }
 
 
 }
@@ -1,19 +1,26 @@
 //! A platform agnostic driver to interface with MSA301 digital accelerometer module.
 //!
 //! TO DO:
-//! * add new_with_configuration, so that new doesn't need one and uses the default
-//! * read_raw should return three i16 values, to be converted with scale in read
 //! * add interrupt functions
 //! * add status reading functions
-//! * begin_accel should check if settings are ok (datarate/bw/power mode) and maybe return an error
 //! 
 //! This driver allows you to:
-//! - configure datarate, bandwidth, power mode, resolution and range, and enable axes
-//! - read the measurements
+//! - use the built-in I2C interface or any transport implementing the `Interface` trait
+//! - use several sensors on one bus through a TCA9548A I2C multiplexer
+//! - verify the device ID and soft reset the sensor
+//! - change settings without read-modify-write round trips, using a shadow copy of the writable registers
+//! - configure datarate, bandwidth, resolution and range, and enable axes
+//! - switch between normal, low power and suspend modes, tracked in the driver's type
+//! - reverse axis polarity and swap X/Y axes
+//! - configure active (any-motion), tap, orientation and freefall detection, and read the orientation
+//! - read the configuration back from the sensor and compare it with the driver's
+//! - read the measurements, raw or scaled according to the range and resolution, or quantized to 4 bytes
+//! - enable interrupts and route them to the INT pin
+//! - read the measurements and the status registers in a single transfer
 //! 
 //! ### Usage
 //! 
-//! ```rust
+//! ```rust,ignore
 //! use rppal::i2c::I2c;
 //! use msa301::*;
 //! use msa301::config::AccelConfig;
@@ -23,10 +30,7 @@
 //! 
 //!     let i2c = I2c::new().unwrap();
 //!     // create a new driver instance with the I2C interface and default configuration settings      
-//!     let mut msa301 = MSA301::new(i2c,
-//!                              AccelConfig {                                
-//!                                 ..Default::default()
-//!                             }).unwrap();
+//!     let mut msa301 = MSA301::new(i2c).unwrap();
 //! 
 //! loop {
 //!         let (x,y,z) = msa301.read_accel().unwrap(); 
@@ -35,6 +39,32 @@
 //! }
 //! ```
 //! 
+//! ### Without floating point
+//! 
+//! The readings in g are `f32`. On MCUs without FPU disable the default `float` feature
+//! and read the measurements in mg with integer math:
+//! 
+//! ```rust,ignore
+//! let (x,y,z) = msa301.read_accel_mg().unwrap(); // i32, mg
+//! ```
+//! 
+//! ### embedded-hal 1.0
+//! 
+//! Enable the `eh1` feature to use a HAL implementing `embedded_hal::i2c::I2c` from embedded-hal 1.0:
+//! 
+//! ```rust,ignore
+//! let mut msa301 = MSA301::new_eh1(i2c).unwrap();
+//! ```
+//! 
+//! ### Async
+//! 
+//! Enable the `async` feature to use `MSA301Async` with a HAL implementing `embedded_hal_async::i2c::I2c`:
+//! 
+//! ```rust,ignore
+//! let mut msa301 = MSA301Async::new(i2c).await.unwrap();
+//! let (x,y,z) = msa301.read_accel().await.unwrap();
+//! ```
+//! 
 
 #![no_std]
 //#![deny(warnings, missing_docs)]
@@ -43,61 +73,278 @@ pub mod sensor;
 pub mod config;
 pub mod fifo;
 pub mod interrupt;
+pub mod motion;
 pub mod register;
+pub mod interface;
+pub mod mode;
+#[cfg(feature = "async")]
+pub mod asynch;
 
-use config::AccelConfig;
-use register::{Bitmasks, Registers};
+use config::{AccelConfig, ConfigError};
+use core::marker::PhantomData;
+pub use mode::{LowPower, Normal, Suspend};
+use register::{Bitmasks, RegisterCache, Registers};
+pub use interface::{Interface, I2cInterface, i2c::I2cAddress};
+pub use interface::{MuxChannel, MuxInterface, Tca9548a};
+#[cfg(feature = "eh1")]
+pub use interface::I2cEh1Interface;
+#[cfg(feature = "async")]
+pub use interface::{AsyncInterface, I2cAsyncInterface};
+#[cfg(feature = "async")]
+pub use asynch::MSA301Async;
 
 use embedded_hal as hal;
+use hal::blocking::delay::DelayMs;
 use hal::blocking::i2c::{Write, WriteRead};
 
 /// Sensor's ID
-// const PARTID: u8 = 0x13; // decimal value 19
+const PARTID: u8 = 0x13; // decimal value 19
+
+/// How many times PART_ID is polled while waiting for the sensor to come back after a soft reset
+const RESET_POLL_ATTEMPTS: u8 = 100;
+
+/// Delay before each PART_ID poll after a soft reset, in ms (up to 100 ms in total)
+const RESET_POLL_INTERVAL_MS: u8 = 1;
 
-/// I2C device address
-const DEV_ADDR: u8 = 0b000100110;
+/// Check the PART_ID read from the sensor
+fn check_part_id<E>(id: u8) -> Result<(), Error<E>> {
+    match id {
+        PARTID => Ok(()),
+        id => Err(Error::WrongDevice(id)),
+    }
+}
+
+/// Outcome of the PART_ID poll number `attempt` after a soft reset: `None` to poll again,
+/// or the result once the sensor responds with the correct value or the attempts run out
+fn reset_poll_outcome<E>(result: Result<u8, Error<E>>, attempt: u8) -> Option<Result<(), Error<E>>> {
+    match result {
+        Ok(PARTID) => Some(Ok(())),
+        _ if attempt < RESET_POLL_ATTEMPTS => None,
+        result => Some(result.and_then(check_part_id)),
+    }
+}
 
 /// All possible errors in this crate
 #[derive(Debug)]
 pub enum Error<E> {
-    /// I2C bus error
+    /// Interface (I2C bus) error
     I2C(E),
     /// Invalid input data
     InvalidInputData,
+    /// Unexpected PART_ID, the device is not an MSA301 (contains the ID read)
+    WrongDevice(u8),
+    /// Settings that can't be used together (contains the conflicting settings)
+    InvalidConfig(ConfigError),
+}
+
+impl<E> From<ConfigError> for Error<E> {
+    fn from(error: ConfigError) -> Self {
+        Error::InvalidConfig(error)
+    }
 }
 
-/// Holds the driver instance with I2C interface and configuration struct
-#[derive(Debug, Default)]
-pub struct MSA301<I2C> {
-    /// The concrete I2C device implementation
-    i2c: I2C,
+/// Holds the driver instance with I2C interface, configuration struct and a shadow copy of the writable registers.
+/// The power mode is part of the type (see the `mode` module).
+#[derive(Debug)]
+pub struct MSA301<T, MODE = Normal> {
+    /// The concrete interface implementation (I2C by default)
+    interface: T,
     config: AccelConfig,
+    registers: RegisterCache,
+    _mode: PhantomData<MODE>,
 }
 
-impl <I2C, E> MSA301<I2C>
+impl<I2C, E> MSA301<I2cInterface<I2C>>
 where
     I2C: Write<Error = E> + WriteRead<Error = E>,
 {    
-    /// Create a new instance of the MSA301 driver.
+    /// Create a new instance of the MSA301 driver using the I2C interface and the default configuration.
+    /// 
+    /// ```rust,ignore
+    ///  let mut msa301 = MSA301::new(i2c).unwrap(); 
+    /// ```    
+    /// 
+    /// Returns `Error::WrongDevice` if the PART_ID doesn't match.
+    pub fn new(i2c: I2C) -> Result<Self, Error<E>> {    
+        Self::new_with_configuration(i2c, AccelConfig::default())
+    }
+
+    /// Create a new instance of the MSA301 driver using the I2C interface and a user-specified configuration.
     /// 
-    /// ```rust
-    ///  let mut msa301 = MSA301::new(i2c, 
-    ///                 AccelConfig{
-    ///                     datarate: DataRate::_125Hz,
-    ///                     bandwidth: BandWidth::_62_5Hz,
-    ///                     ..Default::default()},
-    ///                     ).unwrap(); 
+    /// ```rust,ignore
+    ///  let config = AccelConfig::builder().datarate(DataRate::_125Hz).build().unwrap();
+    ///  let mut msa301 = MSA301::new_with_configuration(i2c, config).unwrap(); 
     /// ```    
-    pub fn new(i2c: I2C, config: AccelConfig) -> Result<Self, Error<E>> {    
-        
-        let mut msa = MSA301 {
-            i2c,
-            config: config
-        };        
+    /// 
+    /// The sensor is started in normal mode, use `into_low_power()` or `into_suspend()` to change it.
+    /// The bandwidth is set to half of the datarate.
+    /// Returns `Error::InvalidConfig` if `config.powermode` is not normal or the datarate is not available
+    /// in normal mode, and `Error::WrongDevice` if the PART_ID doesn't match.
+    pub fn new_with_configuration(i2c: I2C, config: AccelConfig) -> Result<Self, Error<E>> {    
+        Self::with_interface(I2cInterface::init(i2c, I2cAddress::Address), config)
+    }
+
+    /// Create a new instance of the MSA301 driver using the I2C interface, issuing a soft reset first,
+    /// so that all the registers are back to their default values before applying the configuration.
+    /// 
+    /// ```rust,ignore
+    ///  let mut msa301 = MSA301::new_with_reset(i2c, AccelConfig::default(), &mut delay).unwrap(); 
+    /// ```    
+    pub fn new_with_reset<D: DelayMs<u8>>(i2c: I2C, config: AccelConfig, delay: &mut D) -> Result<Self, Error<E>> {
+        let mut msa = Self::create(I2cInterface::init(i2c, I2cAddress::Address), config)?;
+        msa.soft_reset(delay)?;
+        Ok(msa)
+    }
+}
+
+impl<I2C, MODE> MSA301<I2cInterface<I2C>, MODE> {
+    /// Destroy driver instance, return I2C bus instance.
+    pub fn destroy(self) -> I2C {
+        self.interface.release()
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl<I2C> MSA301<I2cEh1Interface<I2C>>
+where
+    I2C: embedded_hal_1::i2c::I2c,
+{
+    /// Create a new instance of the MSA301 driver using an embedded-hal 1.0 I2C bus and the default configuration.
+    /// Bus errors are reported as `Error::I2C(ErrorKind)`.
+    /// 
+    /// ```rust,ignore
+    ///  let mut msa301 = MSA301::new_eh1(i2c).unwrap(); 
+    /// ```    
+    /// 
+    /// Returns `Error::WrongDevice` if the PART_ID doesn't match.
+    pub fn new_eh1(i2c: I2C) -> Result<Self, Error<embedded_hal_1::i2c::ErrorKind>> {
+        Self::new_eh1_with_configuration(i2c, AccelConfig::default())
+    }
+
+    /// Create a new instance of the MSA301 driver using an embedded-hal 1.0 I2C bus and a user-specified configuration.
+    pub fn new_eh1_with_configuration(i2c: I2C, config: AccelConfig) -> Result<Self, Error<embedded_hal_1::i2c::ErrorKind>> {
+        Self::with_interface(I2cEh1Interface::init(i2c, I2cAddress::Address), config)
+    }
+
+    /// Create a new instance of the MSA301 driver using an embedded-hal 1.0 I2C bus, issuing a soft reset first.
+    pub fn new_eh1_with_reset<D: embedded_hal_1::delay::DelayNs>(i2c: I2C, config: AccelConfig, delay: &mut D) -> Result<Self, Error<embedded_hal_1::i2c::ErrorKind>> {
+        let mut msa = Self::create(I2cEh1Interface::init(i2c, I2cAddress::Address), config)?;
+        msa.soft_reset_eh1(delay)?;
+        Ok(msa)
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl<I2C, MODE> MSA301<I2cEh1Interface<I2C>, MODE> {
+    /// Destroy driver instance, return I2C bus instance.
+    pub fn destroy(self) -> I2C {
+        self.interface.release()
+    }
+}
+
+impl<T, E> MSA301<T, Normal>
+where
+    T: Interface<Error = E>,
+{
+    /// Create a new instance of the MSA301 driver using any interface implementing the `Interface` trait.
+    /// 
+    /// ```rust,ignore
+    ///  let interface = I2cInterface::init(i2c, I2cAddress::Address);
+    ///  let mut msa301 = MSA301::with_interface(interface, AccelConfig::default()).unwrap(); 
+    /// ```    
+    /// 
+    /// The sensor is started in normal mode.
+    /// Returns `Error::InvalidConfig` if `config.powermode` is not normal or the datarate is not available
+    /// in normal mode, and `Error::WrongDevice` if the PART_ID doesn't match.
+    pub fn with_interface(interface: T, config: AccelConfig) -> Result<Self, Error<E>> {
+        let mut msa = Self::create(interface, config)?;
+        msa.check_device_id()?;
+        msa.sync_from_device()?;
         msa.init()?;        
         Ok(msa)
     }
 
+    /// Driver instance in normal mode with a validated configuration, nothing is written to the sensor yet
+    fn create(interface: T, config: AccelConfig) -> Result<Self, ConfigError> {
+        if config.powermode != PowerMode::Normal {
+            return Err(ConfigError::PowerModeNotAvailable { powermode: config.powermode });
+        }
+        Ok(MSA301 {
+            interface,
+            config: config.validated()?,
+            registers: RegisterCache::default(),
+            _mode: PhantomData,
+        })
+    }
+}
+
+impl<T, E, MODE> MSA301<T, MODE>
+where
+    T: Interface<Error = E>,
+{
+
+    /// Soft reset the sensor, wait until it responds with the correct PART_ID
+    /// and write the current configuration again.
+    /// The PART_ID is polled every 1 ms for up to 100 ms, starting 1 ms after the reset.
+    pub fn soft_reset<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.reset_and_wait(|| delay.delay_ms(RESET_POLL_INTERVAL_MS))
+    }
+
+    /// Soft reset the sensor using an embedded-hal 1.0 delay (see `soft_reset()`)
+    #[cfg(feature = "eh1")]
+    pub fn soft_reset_eh1<D: embedded_hal_1::delay::DelayNs>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.reset_and_wait(|| delay.delay_ms(RESET_POLL_INTERVAL_MS.into()))
+    }
+
+    fn reset_and_wait(&mut self, wait: impl FnMut()) -> Result<(), Error<E>> {
+        self.write_register(Registers::SOFT_RESET, Bitmasks::SOFT_RESET)?;
+        self.wait_for_device(wait)?;
+        self.sync_from_device()?;
+        self.init()
+    }
+
+    /// Read all the writable registers (RES_RANGE to OFFSET_Z) in one transfer into the shadow copy
+    /// kept by the driver. The settings are then changed without reading the registers first,
+    /// and written only if they change.
+    /// 
+    /// It's done when the driver is created and after a soft reset. Call it if the sensor
+    /// may have been reconfigured without this driver (e.g. another driver instance).
+    pub fn sync_from_device(&mut self) -> Result<(), Error<E>> {
+        self.interface
+            .read(RegisterCache::START.addr(), self.registers.buffer_mut())
+            .map_err(Error::I2C)
+    }
+
+    /// Write all the writable registers from the shadow copy to the sensor,
+    /// e.g. to restore the settings after the sensor lost power.
+    pub fn flush(&mut self) -> Result<(), Error<E>> {
+        for register in RegisterCache::WRITABLE {
+            if let Some(data) = self.registers.get(register) {
+                self.interface.write(register.addr(), data).map_err(Error::I2C)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the PART_ID is correct
+    fn check_device_id(&mut self) -> Result<(), Error<E>> {
+        check_part_id(self.get_device_id()?)
+    }
+
+    /// Poll the PART_ID until the sensor responds with the correct value,
+    /// ignoring the errors while it's resetting and calling `wait` before each poll
+    /// (the PART_ID is constant, so the first poll must not run right after the reset)
+    fn wait_for_device(&mut self, mut wait: impl FnMut()) -> Result<(), Error<E>> {
+        let mut attempt = 1;
+        loop {
+            wait();
+            if let Some(result) = reset_poll_outcome(self.get_device_id(), attempt) {
+                return result;
+            }
+            attempt += 1;
+        }
+    }
+
     fn init(&mut self) -> Result<(), Error<E>> {
         self.write_register(Registers::CFG_ODR, self.config.cfg_odr())?;
         self.write_register(Registers::PWR_BW, self.config.pwr_bw())?;
@@ -105,9 +352,9 @@ where
         Ok(())
     }
 
-    /// Read the current configuration of the sensor
+    /// Get the configuration kept by the driver (see `read_config_from_device()` for the sensor's actual settings)
     /// 
-    /// ```rust
+    /// ```rust,ignore
     /// println!("{:?}", msa301.get_config().unwrap());
     /// ```
     /// 
@@ -123,14 +370,18 @@ where
     }
 
     /// Destroy driver instance, return interface instance.
-    pub fn destroy(self) -> I2C {
-        self.i2c
+    pub fn release(self) -> T {
+        self.interface
     }
 
-    /// Write to a register
+    /// Write to a register, skipped if the shadow copy shows it already has this value
     fn write_register(&mut self, address: Registers, data: u8) -> Result<(), Error<E>> {
-        let payload: [u8; 2] = [address.addr(), data];
-        self.i2c.write(DEV_ADDR, &payload).map_err(Error::I2C)
+        if !self.registers.needs_write(address, data) {
+            return Ok(());
+        }
+        self.interface.write(address.addr(), data).map_err(Error::I2C)?;
+        self.registers.set(address, data);
+        Ok(())
     }
 
     // === REMOVE PUB LATER ===
@@ -138,34 +389,34 @@ where
     /// Read from a register    
     pub fn read_register(&mut self, address: Registers) -> Result<u8, Error<E>> {
         let mut data: [u8; 1] = [0];
-        self.i2c
-            .write_read(DEV_ADDR, &[address.addr()], &mut data)
+        self.interface
+            .read(address.addr(), &mut data)
             .map_err(Error::I2C)
             .and(Ok(data[0]))
     }
 
 
 
+    /// Replace the bits selected by the bitmask with the new value,
+    /// starting from the shadow copy (the register is read only if it's not cached)
+    fn modify_register(&mut self, address: Registers, bitmask: u8, value: u8) -> Result<(), Error<E>> {
+        let data = match self.registers.get(address) {
+            Some(data) => data,
+            None => self.read_register(address)?,
+        };
+        self.write_register(address, (data & !bitmask) | value)
+    }
+
     // === THESE FUNCTIONS MAY NOT BE NECESSARY ===
 
     /// Set specific bits using a bitmask
     fn set_register_bit_flag(&mut self, address: Registers, bitmask: u8) -> Result<(), Error<E>> {
-        let data = self.read_register(address)?;
-        if (data & bitmask) == 0 {
-            self.write_register(address, data | bitmask)            
-        } else {
-            Ok(())
-        }
+        self.modify_register(address, bitmask, bitmask)
     }
 
     /// Clear specific bits using a bitmask
     fn clear_register_bit_flag(&mut self, address: Registers, bitmask: u8) -> Result<(), Error<E>> {
-        let data = self.read_register(address)?;
-        if (data & bitmask) != 0 {
-            self.write_register(address, data & !bitmask)
-        } else {
-            Ok(())
-        }
+        self.modify_register(address, bitmask, 0)
     }
 
     /// Check if specific bits are set.
@@ -179,29 +430,41 @@ where
 
 /// Output data rate and power mode selection (ODR). (see page 23)
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataRate {    
     /// 1 Hz (not available in normal mode)
+    #[cfg_attr(feature = "serde", serde(rename = "1Hz"))]
     _1Hz = 0b0000,
     /// 1.95 Hz (not available in normal mode)
+    #[cfg_attr(feature = "serde", serde(rename = "1.95Hz"))]
     _1_95Hz = 0b0001,
     /// 3.90 Hz
+    #[cfg_attr(feature = "serde", serde(rename = "3.90Hz"))]
     _3_90Hz = 0b0010,
     /// 7.81 Hz
+    #[cfg_attr(feature = "serde", serde(rename = "7.81Hz"))]
     _7_81Hz = 0b0011,
     /// 15.63 Hz    
+    #[cfg_attr(feature = "serde", serde(rename = "15.63Hz"))]
     _15_63Hz = 0b0100,
     /// 31.25Hz
+    #[cfg_attr(feature = "serde", serde(rename = "31.25Hz"))]
     _31_25Hz = 0b0101,
     // 62.5Hz
+    #[cfg_attr(feature = "serde", serde(rename = "62.5Hz"))]
     _62_5Hz = 0b0110,
     // 125Hz 
+    #[cfg_attr(feature = "serde", serde(rename = "125Hz"))]
     _125Hz = 0b0111,
     // 250Hz 
+    #[cfg_attr(feature = "serde", serde(rename = "250Hz"))]
     _250Hz = 0b1000,
     // 500Hz (not available in low power mode)
+    #[cfg_attr(feature = "serde", serde(rename = "500Hz"))]
     _500Hz = 0b1001,
     // 1000Hz (not available in low power mode)
+    #[cfg_attr(feature = "serde", serde(rename = "1000Hz"))]
     _1000Hz = 0b1010,    
 }
 
@@ -209,29 +472,75 @@ impl DataRate {
     pub fn value(self) -> u8 {
         self as u8
     }
+
+    /// Bandwidth used in normal mode (half of the datarate, 1.95 Hz at the lowest)
+    pub fn half_bandwidth(self) -> BandWidth {
+        use DataRate::*;
+        match self {
+            _1Hz | _1_95Hz | _3_90Hz => BandWidth::_1_95Hz,
+            _7_81Hz => BandWidth::_3_90Hz,
+            _15_63Hz => BandWidth::_7_81Hz,
+            _31_25Hz => BandWidth::_15_63Hz,
+            _62_5Hz => BandWidth::_31_25Hz,
+            _125Hz => BandWidth::_62_5Hz,
+            _250Hz => BandWidth::_125Hz,
+            _500Hz => BandWidth::_250Hz,
+            _1000Hz => BandWidth::_500Hz,
+        }
+    }
+}
+
+/// Decode the datarate from the CFG_ODR register value (0b1011 to 0b1111 are 1000 Hz too)
+impl From<u8> for DataRate {
+    fn from(data: u8) -> Self {
+        use DataRate::*;
+        match data & Bitmasks::ODR_MASK {
+            0b0000 => _1Hz,
+            0b0001 => _1_95Hz,
+            0b0010 => _3_90Hz,
+            0b0011 => _7_81Hz,
+            0b0100 => _15_63Hz,
+            0b0101 => _31_25Hz,
+            0b0110 => _62_5Hz,
+            0b0111 => _125Hz,
+            0b1000 => _250Hz,
+            0b1001 => _500Hz,
+            _ => _1000Hz,
+        }
+    }
 }
 
 /// Low power bandwidth. (see page 23)
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BandWidth {        
     /// 1.95 Hz 
+    #[cfg_attr(feature = "serde", serde(rename = "1.95Hz"))]
     _1_95Hz = 0b0010,
     /// 3.90 Hz
+    #[cfg_attr(feature = "serde", serde(rename = "3.90Hz"))]
     _3_90Hz = 0b0011,
     /// 7.81 Hz
+    #[cfg_attr(feature = "serde", serde(rename = "7.81Hz"))]
     _7_81Hz = 0b0100,
     /// 15.63 Hz    
+    #[cfg_attr(feature = "serde", serde(rename = "15.63Hz"))]
     _15_63Hz = 0b0101,
     /// 31.25Hz
+    #[cfg_attr(feature = "serde", serde(rename = "31.25Hz"))]
     _31_25Hz = 0b0110,
     // 62.5Hz
+    #[cfg_attr(feature = "serde", serde(rename = "62.5Hz"))]
     _62_5Hz = 0b0111,
     // 125Hz 
+    #[cfg_attr(feature = "serde", serde(rename = "125Hz"))]
     _125Hz = 0b1000,
     // 250Hz 
+    #[cfg_attr(feature = "serde", serde(rename = "250Hz"))]
     _250Hz = 0b1001,
     // 500Hz
+    #[cfg_attr(feature = "serde", serde(rename = "500Hz"))]
     _500Hz = 0b1010,
 }
 
@@ -240,10 +549,29 @@ impl BandWidth {
         (self as u8) << 1 // shifted into position
     }
 }
+
+/// Decode the bandwidth from the PWR_BW register value (0b0000 and 0b0001 are 1.95 Hz too, 0b1011 to 0b1111 are 500 Hz too)
+impl From<u8> for BandWidth {
+    fn from(data: u8) -> Self {
+        use BandWidth::*;
+        match (data & Bitmasks::BW_MASK) >> 1 {
+            0b0000..=0b0010 => _1_95Hz,
+            0b0011 => _3_90Hz,
+            0b0100 => _7_81Hz,
+            0b0101 => _15_63Hz,
+            0b0110 => _31_25Hz,
+            0b0111 => _62_5Hz,
+            0b1000 => _125Hz,
+            0b1001 => _250Hz,
+            _ => _500Hz,
+        }
+    }
+}
  
 /// Power mode (see page 23)
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PowerMode {
     /// Normal mode
     Normal = 0b00,
@@ -259,17 +587,33 @@ impl PowerMode {
     }
 }
 
+/// Decode the power mode from the PWR_BW register value (0b11 is suspend mode too)
+impl From<u8> for PowerMode {
+    fn from(data: u8) -> Self {
+        match (data & Bitmasks::PWR_MASK) >> 6 {
+            0b00 => PowerMode::Normal,
+            0b01 => PowerMode::LowPower,
+            _ => PowerMode::Suspend,
+        }
+    }
+}
+
 /// Resolution of X/Y/Z axes. (see page 22)
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Res {
     /// 14-bit
+    #[cfg_attr(feature = "serde", serde(rename = "14bit"))]
     _14bit = 0b00,
     /// 12-bit
+    #[cfg_attr(feature = "serde", serde(rename = "12bit"))]
     _12bit = 0b01,
     /// 10-bit
+    #[cfg_attr(feature = "serde", serde(rename = "10bit"))]
     _10bit = 0b10,
     /// 8-bit
+    #[cfg_attr(feature = "serde", serde(rename = "8bit"))]
     _8bit = 0b11,
 }
 
@@ -277,19 +621,48 @@ impl Res {
     pub fn value(self) -> u8 {
         (self as u8) << 2 // shifted into the correct position
     }
+
+    /// Return the number of bits of the measurements
+    pub fn bits(self) -> u8 {
+        use Res::*;
+        match self {
+            _14bit => 14,
+            _12bit => 12,
+            _10bit => 10,
+            _8bit => 8,
+        }
+    }
+}
+
+/// Decode the resolution from the RES_RANGE register value
+impl From<u8> for Res {
+    fn from(data: u8) -> Self {
+        use Res::*;
+        match (data & Bitmasks::RESOLUTION) >> 2 {
+            0b00 => _14bit,
+            0b01 => _12bit,
+            0b10 => _10bit,
+            _ => _8bit,
+        }
+    }
 }
 
 /// Acceleration range of X/Y/Z axes. (see page 23)
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Range {
     /// +/-2g
+    #[cfg_attr(feature = "serde", serde(rename = "2g"))]
     _2g = 0b00,
     /// +/-4g
+    #[cfg_attr(feature = "serde", serde(rename = "4g"))]
     _4g = 0b01,
     /// +/-8g
+    #[cfg_attr(feature = "serde", serde(rename = "8g"))]
     _8g = 0b10,
     /// +/-16g
+    #[cfg_attr(feature = "serde", serde(rename = "16g"))]
     _16g = 0b11,
 }
 
@@ -298,7 +671,7 @@ impl Range {
         self as u8 // shifted into the correct position
     }
     
-    /// Return sensitivity value corresponding to the selected range
+    /// Return sensitivity value (LSB per g) corresponding to the selected range at 14-bit resolution
     pub fn sensitivity(self) -> f32 {
         use Range::*;
         match self {
@@ -308,10 +681,35 @@ impl Range {
             _16g => 512.0,
         }
     }
+
+    /// Return the full scale value in mg corresponding to the selected range
+    pub fn full_scale_mg(self) -> u32 {
+        use Range::*;
+        match self {
+            _2g => 2000,
+            _4g => 4000,
+            _8g => 8000,
+            _16g => 16000,
+        }
+    }
+}
+
+/// Decode the range from the RES_RANGE register value
+impl From<u8> for Range {
+    fn from(data: u8) -> Self {
+        use Range::*;
+        match data & Bitmasks::FS {
+            0b00 => _2g,
+            0b01 => _4g,
+            0b10 => _8g,
+            _ => _16g,
+        }
+    }
 }
 /// Interrupt active setting for the INT1 pin: active high (default) or active low
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum IntActive {
     /// Active high
     High,
@@ -321,17 +719,17 @@ pub enum IntActive {
 
 impl IntActive {
     pub fn status(self) -> bool {
-        let status = match self {
+        match self {
             IntActive::High => false,
             IntActive::Low => true,
-        };
-        status
+        }
     }
 }
 
 /// Interrupt pad setting for INT1 pin: push-pull (default) or open-drain.
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum IntPin {
     /// Push-pull
     PushPull,
@@ -341,17 +739,17 @@ pub enum IntPin {
 
 impl IntPin {
     pub fn status(self) -> bool {
-        let status = match self {
+        match self {
             IntPin::PushPull => false,
             IntPin::OpenDrain => true,
-        };
-        status
+        }
     }
 }
  
 /// Settings for various bit flags that can be Enabled (active) or Disabled (inactive)
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Flag {
     /// Enable (bit set)    
     Enable,
@@ -361,37 +759,49 @@ pub enum Flag {
 
 impl Flag {
     pub fn status(self) -> bool {
-        let status = match self {
+        match self {
             Flag::Disable => false,
             Flag::Enable => true,
-        };
-        status
+        }
     }
 }
 
+/// Axis selection
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Axis {
+    /// X axis
+    X,
+    /// Y axis
+    Y,
+    /// Z axis
+    Z,
+}
+
 /// Settings for various bit flags regarding activity and tap detection, which can be either positive or negative
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Sign {
-    /// Positive (bit set)
+    /// Positive (bit cleared)
     Positive,
-    /// Negaitive (bit cleared)
+    /// Negative (bit set)
     Negative,
 }
 
 impl Sign {
     pub fn status(self) -> bool {
-        let status = match self {
-            Sign::Negative => false,
-            Sign::Positive => true,
-        };
-        status
+        match self {
+            Sign::Positive => false,
+            Sign::Negative => true,
+        }
     }
 }
 
 /// Settings for various bit flags regarding axis polarity and output swapping, which can be either positive or negative
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Polarity {
     /// Normal (not reversed) (bit cleared)
     Normal,
@@ -401,17 +811,17 @@ pub enum Polarity {
 
 impl Polarity{
     pub fn status(self) -> bool {
-        let status = match self {
+        match self {
             Polarity::Normal => false,
             Polarity::Reversed => true,
-        };
-        status
+        }
     }
 }
 
 /// Orientation mode of the x/y axes selection. (see page 22)
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OrientXY {
     /// Portrait upright
     PortraitUpright = 0b00,
@@ -425,13 +835,14 @@ pub enum OrientXY {
 
 impl OrientXY {
     pub fn value(self) -> u8 {
-        (self as u8) << 5 // shifted into the correct position, can be used directly
+        (self as u8) << 4 // shifted into the correct position, can be used directly
     }
 }
 
 /// Orientation mode of the z axis selection. (see page 22)
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OrientZ {
     /// Upward looking
     Upward = 0b00,
@@ -441,31 +852,39 @@ pub enum OrientZ {
 
 impl OrientZ {
     pub fn value(self) -> u8 {
-        self as u8 // shifted into the correct position, can be used directly
+        (self as u8) << 6 // shifted into the correct position, can be used directly
     }
 }
 
 /// Interrupt latching (see page 25)
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum IntLatch {
     /// Non-latched
     NonLatched = 0b0000,
     /// temporary latched 250ms 0b0001
+    #[cfg_attr(feature = "serde", serde(rename = "250ms"))]
     TempLatch_250ms = 0b0001,
     /// temporary latched 500ms 0b0010
+    #[cfg_attr(feature = "serde", serde(rename = "500ms"))]
     TempLatch_500ms = 0b0010, 
     /// temporary latched 1s = 0b0011, 
+    #[cfg_attr(feature = "serde", serde(rename = "1s"))]
     TempLatch_1s = 0b0011, 
     /// temporary latched 2s = 0b0100, 
+    #[cfg_attr(feature = "serde", serde(rename = "2s"))]
     TempLatch_2s = 0b0100, 
     /// temporary latched 4s = 0b0101, 
+    #[cfg_attr(feature = "serde", serde(rename = "4s"))]
     TempLatch_4s = 0b0101, 
     /// temporary latched 8s = 0b0110, 
+    #[cfg_attr(feature = "serde", serde(rename = "8s"))]
     TempLatch_8s = 0b0110, 
     /// latched = 0b0111, 
     Latched = 0b0111,  
     /// temporary latched 1ms = 0b1001, 
+    #[cfg_attr(feature = "serde", serde(rename = "1ms"))]
     TempLatch_1ms = 0b1001, 
     /// temporary latched 1ms = 0b1010, 
  
@@ -473,12 +892,16 @@ pub enum IntLatch {
 
     // TempLatch_1ms = 0b1010, 
     /// temporary latched 2ms = 0b1011, 
+    #[cfg_attr(feature = "serde", serde(rename = "2ms"))]
     TempLatch_2ms = 0b1011, 
     /// temporary latched 25ms = 0b1100, 
+    #[cfg_attr(feature = "serde", serde(rename = "25ms"))]
     TempLatch_25ms = 0b1100, 
     /// temporary latched 50ms = 0b1101, 
+    #[cfg_attr(feature = "serde", serde(rename = "50ms"))]
     TempLatch_50ms = 0b1101, 
     /// temporary latched 100ms = 0b1110, 
+    #[cfg_attr(feature = "serde", serde(rename = "100ms"))]
     TempLatch_100ms = 0b1110, 
 }
 
@@ -486,16 +909,39 @@ impl IntLatch {
     pub fn value(self) -> u8 {
         self as u8 // shifted into the correct position
     }
+
+    /// Decode the 4 latching bits of the INT_LATCH register
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        use IntLatch::*;
+        match bits {
+            0b0001 => TempLatch_250ms,
+            0b0010 => TempLatch_500ms,
+            0b0011 => TempLatch_1s,
+            0b0100 => TempLatch_2s,
+            0b0101 => TempLatch_4s,
+            0b0110 => TempLatch_8s,
+            0b0111 | 0b1111 => Latched,
+            0b1001 | 0b1010 => TempLatch_1ms,
+            0b1011 => TempLatch_2ms,
+            0b1100 => TempLatch_25ms,
+            0b1101 => TempLatch_50ms,
+            0b1110 => TempLatch_100ms,
+            _ => NonLatched,
+        }
+    }
 }
 
 /// Tap quiet duration. (see page 27)
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TapQuiet {
     /// Tap quiet duration 30ms
+    #[cfg_attr(feature = "serde", serde(rename = "30ms"))]
     _30ms = 0b00,
-    /// Tap quiet duration 50ms
-    _50ms =  0b01,
+    /// Tap quiet duration 20ms
+    #[cfg_attr(feature = "serde", serde(rename = "20ms"))]
+    _20ms =  0b01,
 }
 
 impl TapQuiet {
@@ -507,10 +953,13 @@ impl TapQuiet {
 /// Tap shock duration. (see page 27)
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TapShock {
-    /// Tap shock duration 30ms
-    _50ms = 0b00,
     /// Tap shock duration 50ms
+    #[cfg_attr(feature = "serde", serde(rename = "50ms"))]
+    _50ms = 0b00,
+    /// Tap shock duration 70ms
+    #[cfg_attr(feature = "serde", serde(rename = "70ms"))]
     _70ms =  0b01,
 }
 
@@ -523,22 +972,31 @@ impl TapShock {
 /// Time window length for the second shock (see page 27)
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TapDur {
     /// 50 ms
+    #[cfg_attr(feature = "serde", serde(rename = "50ms"))]
     _50ms = 0b000,
     /// 100 ms
+    #[cfg_attr(feature = "serde", serde(rename = "100ms"))]
     _100ms = 0b001,
     /// 150 ms
+    #[cfg_attr(feature = "serde", serde(rename = "150ms"))]
     _150ms = 0b010,
     /// 200 ms
+    #[cfg_attr(feature = "serde", serde(rename = "200ms"))]
     _200ms = 0b011,
     /// 250 ms
+    #[cfg_attr(feature = "serde", serde(rename = "250ms"))]
     _250ms = 0b100,
     /// 375 ms
+    #[cfg_attr(feature = "serde", serde(rename = "375ms"))]
     _375ms = 0b101,
     /// 500 ms
+    #[cfg_attr(feature = "serde", serde(rename = "500ms"))]
     _500ms = 0b110,
     /// 700 ms
+    #[cfg_attr(feature = "serde", serde(rename = "700ms"))]
     _700ms = 0b111,
 }
 
@@ -548,55 +1006,9 @@ impl TapDur {
     }
 }
 
-/// Active interrupt threshold. (see page 26)
-#[allow(non_camel_case_types)]
-#[derive(Debug, Clone, Copy)]
-
-// --- ?????? --- CHECK ADAFRUIT DRIVER
-pub enum ThreshRange {    
-    /*
-    /// +/-2g
-    _2g = 0b00,
-    /// +/-4g
-    _4g = 0b01,
-    /// +/-8g
-    _8g = 0b10,
-    /// +/-16g
-    _16g = 0b11,
-    */
-}
-
-impl ThreshRange {
-    pub fn value(self) -> u8 {
-        self as u8 // shifted into the correct position
-    }
-}
-
-/// Tap threshold range. (see page 27)
-#[allow(non_camel_case_types)]
-#[derive(Debug, Clone, Copy)]
-
-// --- ?????? --- CHECK ADAFRUIT DRIVER
-pub enum TapThresh {    
-    /*
-    /// +/-2g
-    _2g = 0b00,
-    /// +/-4g
-    _4g = 0b01,
-    /// +/-8g
-    _8g = 0b10,
-    /// +/-16g
-    _16g = 0b11,
-    */
-}
-
-impl TapThresh {
-    pub fn value(self) -> u8 {
-        self as u8 // shifted into the correct position
-    }
-}
-
 /// Orientation interrupt blocking mode
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OrientBlock {
     /// No blocking 
     NoBlock = 0b00,
@@ -615,6 +1027,8 @@ impl OrientBlock {
 }
     
 /// Orientation interrupt threshold setting
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OrientMode {
     /// Symmetrical 
     Symmetrical = 0b00,
@@ -635,6 +1049,7 @@ impl OrientMode {
 /// Freefall mode. (see page 26)
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FreefallMode {
     /// Single mode
     Single,
@@ -644,10 +1059,100 @@ pub enum FreefallMode {
 
 impl FreefallMode {
     pub fn status(self) -> bool {
-        let status = match self {
+        match self {
             FreefallMode::Single => false,
             FreefallMode::Sum => true,
-        };
-        status
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn datarate_decoding() {
+        assert_eq!(DataRate::from(0b0000), DataRate::_1Hz);
+        assert_eq!(DataRate::from(0b1001), DataRate::_500Hz);
+        assert_eq!(DataRate::from(0b1010), DataRate::_1000Hz);
+        // reserved codes, e.g. the power-on value 0x0F
+        for code in 0b1011..=0b1111 {
+            assert_eq!(DataRate::from(code), DataRate::_1000Hz);
+        }
+        // the axes bits are masked
+        assert_eq!(DataRate::from(0b1110_0111), DataRate::_125Hz);
+    }
+
+    #[test]
+    fn bandwidth_decoding() {
+        assert_eq!(BandWidth::from(0b0000_0000), BandWidth::_1_95Hz);
+        assert_eq!(BandWidth::from(0b0000_0010), BandWidth::_1_95Hz);
+        assert_eq!(BandWidth::from(0b0000_0100), BandWidth::_1_95Hz);
+        assert_eq!(BandWidth::from(0b0001_0010), BandWidth::_250Hz);
+        assert_eq!(BandWidth::from(0b0001_0100), BandWidth::_500Hz);
+        // reserved codes, e.g. the power-on value 0xDE
+        for code in 0b1011..=0b1111 {
+            assert_eq!(BandWidth::from(code << 1), BandWidth::_500Hz);
+        }
+        assert_eq!(BandWidth::from(0xDE), BandWidth::_500Hz);
+        // the power mode and reserved bits are masked
+        assert_eq!(BandWidth::from(0b1111_0001), BandWidth::_125Hz);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn powermode_decoding() {
+        assert_eq!(PowerMode::from(0b0000_0000), PowerMode::Normal);
+        assert_eq!(PowerMode::from(0b0101_1110), PowerMode::LowPower);
+        assert_eq!(PowerMode::from(0b1000_0000), PowerMode::Suspend);
+        assert_eq!(PowerMode::from(0xDE), PowerMode::Suspend);
+    }
+
+    #[test]
+    fn resolution_and_range_decoding() {
+        assert_eq!(Res::from(0b0000_0000), Res::_14bit);
+        assert_eq!(Res::from(0b0000_0100), Res::_12bit);
+        assert_eq!(Res::from(0b0000_1000), Res::_10bit);
+        assert_eq!(Res::from(0b1111_1100), Res::_8bit);
+        assert_eq!(Range::from(0b0000_0000), Range::_2g);
+        assert_eq!(Range::from(0b0000_0001), Range::_4g);
+        assert_eq!(Range::from(0b0000_0010), Range::_8g);
+        assert_eq!(Range::from(0b1111_1111), Range::_16g);
+    }
+
+    #[test]
+    fn encoding_round_trip() {
+        let config = config::AccelConfig::builder()
+            .powermode(PowerMode::LowPower)
+            .datarate(DataRate::_31_25Hz)
+            .bandwidth(BandWidth::_15_63Hz)
+            .resolution(Res::_10bit)
+            .range(Range::_8g)
+            .build()
+            .unwrap();
+        assert_eq!(DataRate::from(config.cfg_odr()), config.datarate);
+        assert_eq!(BandWidth::from(config.pwr_bw()), config.bandwidth);
+        assert_eq!(PowerMode::from(config.pwr_bw()), config.powermode);
+        assert_eq!(Res::from(config.res_range()), config.resolution);
+        assert_eq!(Range::from(config.res_range()), config.range);
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn settings_named_after_their_values() {
+        assert_eq!(serde_json::to_string(&DataRate::_62_5Hz).unwrap(), r#""62.5Hz""#);
+        assert_eq!(serde_json::to_string(&IntLatch::TempLatch_250ms).unwrap(), r#""250ms""#);
+        assert_eq!(serde_json::to_string(&IntLatch::TempLatch_1s).unwrap(), r#""1s""#);
+        let latch: IntLatch = serde_json::from_str(r#""100ms""#).unwrap();
+        assert_eq!(latch.value(), IntLatch::TempLatch_100ms.value());
+    }
+
+    #[test]
+    fn other_settings_named_after_their_variants() {
+        assert_eq!(serde_json::to_string(&IntLatch::NonLatched).unwrap(), r#""NonLatched""#);
+        assert_eq!(serde_json::to_string(&IntPin::OpenDrain).unwrap(), r#""OpenDrain""#);
+        assert_eq!(serde_json::to_string(&OrientBlock::ZaxisBlock).unwrap(), r#""ZaxisBlock""#);
+    }
+}
@@ -0,0 +1,114 @@
+//! Power mode type states
+//!
+//! The driver carries its power mode in its type: `MSA301<T, Normal>`, `MSA301<T, LowPower>` or `MSA301<T, Suspend>`.
+//! Changing the power mode consumes the driver and returns it in the new state, so that the measurements
+//! can only be read in the active (normal and low power) modes, and the bandwidth can only be set in low power mode.
+//! The change is rejected with `Error::InvalidConfig` if the current datarate is not available in the new mode,
+//! before anything is written, so set a suitable datarate first (e.g. 250 Hz or lower before switching to low power mode).
+//! On failure the driver is handed back in its current state together with the error, so it is not lost.
+//!
+//! ```rust,ignore
+//! let mut msa301 = MSA301::new(i2c).unwrap(); // MSA301<_, Normal>
+//! msa301.set_datarate(DataRate::_125Hz).unwrap(); // 500 Hz is not available in low power mode
+//! let mut msa301 = msa301.into_low_power().map_err(|(_, e)| e).unwrap();
+//! msa301.set_bandwidth(BandWidth::_31_25Hz).unwrap();
+//! let (x,y,z) = msa301.read_accel().unwrap();
+//! let msa301 = msa301.into_suspend().map_err(|(_, e)| e).unwrap(); // read_accel() is not available anymore
+//! ```
+
+use super::*;
+use core::marker::PhantomData;
+
+/// Normal mode
+#[derive(Debug, Default)]
+pub struct Normal;
+
+/// Low power mode
+#[derive(Debug, Default)]
+pub struct LowPower;
+
+/// Suspend mode
+#[derive(Debug, Default)]
+pub struct Suspend;
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for super::Normal {}
+    impl Sealed for super::LowPower {}
+    impl Sealed for super::Suspend {}
+}
+
+/// Power mode type state
+pub trait PowerState: private::Sealed {
+    /// Power mode written to the sensor in this state
+    const POWER_MODE: PowerMode;
+}
+
+/// Power modes in which the sensor is measuring
+pub trait ActiveMode: PowerState {}
+
+impl PowerState for Normal {
+    const POWER_MODE: PowerMode = PowerMode::Normal;
+}
+
+impl PowerState for LowPower {
+    const POWER_MODE: PowerMode = PowerMode::LowPower;
+}
+
+impl PowerState for Suspend {
+    const POWER_MODE: PowerMode = PowerMode::Suspend;
+}
+
+impl ActiveMode for Normal {}
+impl ActiveMode for LowPower {}
+
+impl<T, E, MODE> MSA301<T, MODE>
+where
+    T: Interface<Error = E>,
+    MODE: PowerState,
+{
+    /// Switch to normal mode
+    pub fn into_normal(self) -> Result<MSA301<T, Normal>, (Self, Error<E>)> {
+        self.into_mode()
+    }
+
+    /// Switch to low power mode
+    pub fn into_low_power(self) -> Result<MSA301<T, LowPower>, (Self, Error<E>)> {
+        self.into_mode()
+    }
+
+    /// Switch to suspend mode
+    pub fn into_suspend(self) -> Result<MSA301<T, Suspend>, (Self, Error<E>)> {
+        self.into_mode()
+    }
+
+    /// Write the power mode of the new state and return the driver in that state.
+    /// Returns the driver unchanged with `Error::InvalidConfig` if the current datarate is not available
+    /// in the new power mode, or with the bus error if the write failed.
+    fn into_mode<NEW: PowerState>(mut self) -> Result<MSA301<T, NEW>, (Self, Error<E>)> {
+        let config = match self.config.with_powermode(NEW::POWER_MODE) {
+            Ok(config) => config,
+            Err(e) => return Err((self, e.into())),
+        };
+        if let Err(e) = self.apply_config(config) {
+            return Err((self, e));
+        }
+        Ok(MSA301 {
+            interface: self.interface,
+            config: self.config,
+            registers: self.registers,
+            _mode: PhantomData,
+        })
+    }
+}
+
+impl<T, E> MSA301<T, LowPower>
+where
+    T: Interface<Error = E>,
+{
+    /// Set bandwidth (only in low power mode, in normal mode is always half of the datarate)
+    pub fn set_bandwidth(&mut self, bandwidth: BandWidth) -> Result<(), Error<E>> {
+        let config = self.config.with_bandwidth(bandwidth)?;
+        self.apply_config(config)
+    }
+}
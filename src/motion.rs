@@ -0,0 +1,439 @@
+//! Various functions related to motion detection: active (any-motion), tap, orientation and freefall detection settings
+//!
+//! Thresholds are given in mg and converted into register values according to the
+//! currently configured range, so they need to be written again after changing the range.
+
+use super::*;
+
+/// Active (any-motion) detection settings (see page 26)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActiveConfig {
+    /// Threshold in mg (1 LSB is 3.91/7.81/15.63/31.25 mg at 2/4/8/16g range)
+    pub threshold_mg: u16,
+    /// Duration in ms (1 to 4 ms, i.e. consecutive samples above the threshold)
+    pub duration_ms: u8,
+}
+
+impl Default for ActiveConfig {
+    fn default() -> Self {
+        // power-on values: ACTIVE_DUR = 0x00, ACTIVE_THS = 0x14 at 2g range
+        ActiveConfig {
+            threshold_mg: 78,
+            duration_ms: 1,
+        }
+    }
+}
+
+impl ActiveConfig {
+    /// Value to be written to the ACTIVE_DUR register
+    pub(crate) fn active_dur<E>(&self) -> Result<u8, Error<E>> {
+        match self.duration_ms {
+            1..=4 => Ok(self.duration_ms - 1),
+            _ => Err(Error::InvalidInputData),
+        }
+    }
+
+    /// Value to be written to the ACTIVE_THS register, 1 LSB = full scale / 512
+    pub(crate) fn active_ths<E>(&self, range: Range) -> Result<u8, Error<E>> {
+        mg_to_code(self.threshold_mg, range.full_scale_mg(), 512, u8::MAX)
+    }
+
+    /// Decode the contents of the ACTIVE_DUR and ACTIVE_THS registers
+    pub(crate) fn from_registers(active_dur: u8, active_ths: u8, range: Range) -> Self {
+        ActiveConfig {
+            threshold_mg: code_to_mg(active_ths, range.full_scale_mg(), 512),
+            duration_ms: (active_dur & Bitmasks::ACTIVE_DUR) + 1,
+        }
+    }
+}
+
+/// Tap and double tap detection settings (see page 27)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TapConfig {
+    /// Tap quiet duration
+    pub quiet: TapQuiet,
+    /// Tap shock duration
+    pub shock: TapShock,
+    /// Time window for the second shock of a double tap
+    pub duration: TapDur,
+    /// Threshold in mg (1 LSB is 62.5/125/250/500 mg at 2/4/8/16g range, max 31 LSB)
+    pub threshold_mg: u16,
+}
+
+impl Default for TapConfig {
+    fn default() -> Self {
+        // power-on values: TAP_DUR = 0x04, TAP_THS = 0x0A at 2g range
+        TapConfig {
+            quiet: TapQuiet::_30ms,
+            shock: TapShock::_50ms,
+            duration: TapDur::_250ms,
+            threshold_mg: 625,
+        }
+    }
+}
+
+impl TapConfig {
+    /// Value to be written to the TAP_DUR register
+    pub(crate) fn tap_dur(&self) -> u8 {
+        let mut data: u8 = 0;
+        data |= self.quiet.value();
+        data |= self.shock.value();
+        data |= self.duration.value();
+        data
+    }
+
+    /// Value to be written to the TAP_THS register, 1 LSB = full scale / 32
+    pub(crate) fn tap_ths<E>(&self, range: Range) -> Result<u8, Error<E>> {
+        mg_to_code(self.threshold_mg, range.full_scale_mg(), 32, Bitmasks::TAP_THS)
+    }
+
+    /// Decode the contents of the TAP_DUR and TAP_THS registers
+    pub(crate) fn from_registers(tap_dur: u8, tap_ths: u8, range: Range) -> Self {
+        TapConfig {
+            quiet: match tap_dur & Bitmasks::TAP_QUIET {
+                0 => TapQuiet::_30ms,
+                _ => TapQuiet::_20ms,
+            },
+            shock: match tap_dur & Bitmasks::TAP_SHOCK {
+                0 => TapShock::_50ms,
+                _ => TapShock::_70ms,
+            },
+            duration: match tap_dur & Bitmasks::TAP_DUR {
+                0b000 => TapDur::_50ms,
+                0b001 => TapDur::_100ms,
+                0b010 => TapDur::_150ms,
+                0b011 => TapDur::_200ms,
+                0b100 => TapDur::_250ms,
+                0b101 => TapDur::_375ms,
+                0b110 => TapDur::_500ms,
+                _ => TapDur::_700ms,
+            },
+            threshold_mg: code_to_mg(tap_ths & Bitmasks::TAP_THS, range.full_scale_mg(), 32),
+        }
+    }
+}
+
+/// Orientation recognition settings (see page 27)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrientConfig {
+    /// Blocking mode
+    pub blocking: OrientBlock,
+    /// Symmetrical or asymmetrical threshold mode
+    pub mode: OrientMode,
+    /// Hysteresis in mg (1 LSB is 62.5 mg regardless of range, max 7 LSB)
+    pub hysteresis_mg: u16,
+}
+
+impl Default for OrientConfig {
+    fn default() -> Self {
+        // power-on value: ORIENT_CFG = 0x18
+        OrientConfig {
+            blocking: OrientBlock::ZaxisBlockOrSlope,
+            mode: OrientMode::Symmetrical,
+            hysteresis_mg: 63,
+        }
+    }
+}
+
+impl OrientConfig {
+    /// Value to be written to the ORIENT_CFG register
+    pub(crate) fn orient_cfg<E>(&self) -> Result<u8, Error<E>> {
+        let hysteresis = mg_to_code(self.hysteresis_mg, 1000, 16, 0b111)?;
+        let mut data: u8 = 0;
+        data |= hysteresis << 4;
+        data |= self.blocking.value();
+        data |= self.mode.value();
+        Ok(data)
+    }
+
+    /// Decode the contents of the ORIENT_CFG register
+    pub(crate) fn from_register(orient_cfg: u8) -> Self {
+        OrientConfig {
+            blocking: match (orient_cfg & Bitmasks::ORIENT_BLOCK) >> 2 {
+                0b01 => OrientBlock::ZaxisBlock,
+                0b10 => OrientBlock::ZaxisBlockOrSlope,
+                _ => OrientBlock::NoBlock,
+            },
+            mode: match orient_cfg & Bitmasks::ORIENT_MODE {
+                0b01 => OrientMode::HighAsymmetrical,
+                0b10 => OrientMode::LowAsymmetrical,
+                _ => OrientMode::Symmetrical,
+            },
+            hysteresis_mg: code_to_mg((orient_cfg & Bitmasks::ORIENT_HYST) >> 4, 1000, 16),
+        }
+    }
+}
+
+/// Orientation as recognized by the sensor
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Orientation {
+    /// Orientation of the x/y axes
+    pub xy: OrientXY,
+    /// Orientation of the z axis
+    pub z: OrientZ,
+}
+
+impl Orientation {
+    /// Decode the contents of the ORIENTATION register
+    pub(crate) fn from_register(orientation: u8) -> Self {
+        Orientation {
+            xy: match (orientation & Bitmasks::ORIENT_XY) >> 4 {
+                0b00 => OrientXY::PortraitUpright,
+                0b01 => OrientXY::PortraitUpsideDown,
+                0b10 => OrientXY::LandscapeLeft,
+                _ => OrientXY::LandscapeRight,
+            },
+            z: match orientation & Bitmasks::ORIENT_Z {
+                0 => OrientZ::Upward,
+                _ => OrientZ::Downward,
+            },
+        }
+    }
+}
+
+/// Freefall detection settings (see page 26)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FreefallConfig {
+    /// Duration in ms (2 to 512 ms in 2 ms steps)
+    pub duration_ms: u16,
+    /// Threshold in mg (1 LSB is 7.81 mg regardless of range, max 255 LSB)
+    pub threshold_mg: u16,
+    /// Hysteresis in mg (1 LSB is 125 mg regardless of range, max 3 LSB)
+    pub hysteresis_mg: u16,
+    /// Single (each axis) or sum (sum of all axes) mode
+    pub mode: FreefallMode,
+}
+
+impl Default for FreefallConfig {
+    fn default() -> Self {
+        // power-on values: FRFL_DUR = 0x09, FRFL_THS = 0x30, FRFL_HYS = 0x01
+        FreefallConfig {
+            duration_ms: 20,
+            threshold_mg: 375,
+            hysteresis_mg: 125,
+            mode: FreefallMode::Single,
+        }
+    }
+}
+
+impl FreefallConfig {
+    /// Value to be written to the FRFL_DUR register, duration = (value + 1) * 2 ms
+    pub(crate) fn frfl_dur<E>(&self) -> Result<u8, Error<E>> {
+        match self.duration_ms {
+            2..=512 => Ok((self.duration_ms.div_ceil(2) - 1) as u8),
+            _ => Err(Error::InvalidInputData),
+        }
+    }
+
+    /// Value to be written to the FRFL_THS register, 1 LSB = 7.81 mg
+    pub(crate) fn frfl_ths<E>(&self) -> Result<u8, Error<E>> {
+        mg_to_code(self.threshold_mg, 1000, 128, u8::MAX)
+    }
+
+    /// Value to be written to the FRFL_HYS register, 1 LSB = 125 mg
+    pub(crate) fn frfl_hys<E>(&self) -> Result<u8, Error<E>> {
+        let mut data = mg_to_code(self.hysteresis_mg, 1000, 8, Bitmasks::FREEFALL_HYS)?;
+        if self.mode.status() {
+            data |= Bitmasks::FREEFALL_MODE;
+        }
+        Ok(data)
+    }
+
+    /// Decode the contents of the FRFL_DUR, FRFL_THS and FRFL_HYS registers
+    pub(crate) fn from_registers(frfl_dur: u8, frfl_ths: u8, frfl_hys: u8) -> Self {
+        FreefallConfig {
+            duration_ms: (frfl_dur as u16 + 1) * 2,
+            threshold_mg: code_to_mg(frfl_ths, 1000, 128),
+            hysteresis_mg: code_to_mg(frfl_hys & Bitmasks::FREEFALL_HYS, 1000, 8),
+            mode: match frfl_hys & Bitmasks::FREEFALL_MODE {
+                0 => FreefallMode::Single,
+                _ => FreefallMode::Sum,
+            },
+        }
+    }
+}
+
+/// Convert a value in mg into a register value with `lsb_mg = full_scale_mg / divisor`,
+/// rounded to the nearest step. Values above `max` are rejected.
+pub(crate) fn mg_to_code<E>(mg: u16, full_scale_mg: u32, divisor: u32, max: u8) -> Result<u8, Error<E>> {
+    let code = (mg as u32 * divisor + full_scale_mg / 2) / full_scale_mg;
+    if code > max as u32 {
+        Err(Error::InvalidInputData)
+    } else {
+        Ok(code as u8)
+    }
+}
+
+/// Convert a register value into mg with `lsb_mg = full_scale_mg / divisor`, rounded to the nearest mg
+pub(crate) fn code_to_mg(code: u8, full_scale_mg: u32, divisor: u32) -> u16 {
+    ((code as u32 * full_scale_mg + divisor / 2) / divisor) as u16
+}
+
+impl<T, E, MODE> MSA301<T, MODE>
+where
+    T: Interface<Error = E>,
+{
+    /// Configure active (any-motion) detection threshold and duration.
+    /// The threshold is converted according to the currently configured range.
+    ///
+    /// ```rust,ignore
+    /// msa301.configure_active(ActiveConfig { threshold_mg: 250, duration_ms: 2 }).unwrap();
+    /// msa301.active_xaxis_int(Flag::Enable).unwrap();
+    /// ```
+    ///
+    pub fn configure_active(&mut self, config: ActiveConfig) -> Result<(), Error<E>> {
+        let duration = config.active_dur()?;
+        let threshold = config.active_ths(self.config.range)?;
+        self.write_register(Registers::ACTIVE_DUR, duration)?;
+        self.write_register(Registers::ACTIVE_THS, threshold)?;
+        Ok(())
+    }
+
+    /// Read the active (any-motion) detection settings from the sensor
+    pub fn active_config(&mut self) -> Result<ActiveConfig, Error<E>> {
+        let duration = self.read_register(Registers::ACTIVE_DUR)?;
+        let threshold = self.read_register(Registers::ACTIVE_THS)?;
+        Ok(ActiveConfig::from_registers(duration, threshold, self.config.range))
+    }
+
+    /// Configure tap and double tap detection (quiet, shock and duration timing, and threshold).
+    /// The threshold is converted according to the currently configured range.
+    ///
+    /// ```rust,ignore
+    /// msa301.configure_tap(TapConfig { threshold_mg: 500, ..Default::default() }).unwrap();
+    /// msa301.single_tap_int(Flag::Enable).unwrap();
+    /// ```
+    ///
+    pub fn configure_tap(&mut self, config: TapConfig) -> Result<(), Error<E>> {
+        let threshold = config.tap_ths(self.config.range)?;
+        self.write_register(Registers::TAP_DUR, config.tap_dur())?;
+        self.write_register(Registers::TAP_THS, threshold)?;
+        Ok(())
+    }
+
+    /// Read the tap detection settings from the sensor
+    pub fn tap_config(&mut self) -> Result<TapConfig, Error<E>> {
+        let duration = self.read_register(Registers::TAP_DUR)?;
+        let threshold = self.read_register(Registers::TAP_THS)?;
+        Ok(TapConfig::from_registers(duration, threshold, self.config.range))
+    }
+
+    /// Configure orientation recognition (blocking mode, threshold mode and hysteresis)
+    ///
+    /// ```rust,ignore
+    /// msa301.configure_orientation(OrientConfig { blocking: OrientBlock::ZaxisBlock, ..Default::default() }).unwrap();
+    /// msa301.orient_int(Flag::Enable).unwrap();
+    /// ```
+    ///
+    pub fn configure_orientation(&mut self, config: OrientConfig) -> Result<(), Error<E>> {
+        let data = config.orient_cfg()?;
+        self.write_register(Registers::ORIENT_CFG, data)
+    }
+
+    /// Read the orientation recognition settings from the sensor
+    pub fn orientation_config(&mut self) -> Result<OrientConfig, Error<E>> {
+        let data = self.read_register(Registers::ORIENT_CFG)?;
+        Ok(OrientConfig::from_register(data))
+    }
+
+    /// Read the current orientation
+    ///
+    /// ```rust,ignore
+    /// let orientation = msa301.orientation().unwrap();
+    /// println!("xy: {:?}, z: {:?}", orientation.xy, orientation.z);
+    /// ```
+    ///
+    pub fn orientation(&mut self) -> Result<Orientation, Error<E>> {
+        let data = self.read_register(Registers::ORIENTATION)?;
+        Ok(Orientation::from_register(data))
+    }
+
+    /// Configure freefall detection (duration, threshold, hysteresis and mode)
+    ///
+    /// ```rust,ignore
+    /// msa301.configure_freefall(FreefallConfig { duration_ms: 100, ..Default::default() }).unwrap();
+    /// msa301.freefall_int(Flag::Enable).unwrap();
+    /// ```
+    ///
+    pub fn configure_freefall(&mut self, config: FreefallConfig) -> Result<(), Error<E>> {
+        let duration = config.frfl_dur()?;
+        let threshold = config.frfl_ths()?;
+        let hysteresis = config.frfl_hys()?;
+        self.write_register(Registers::FRFL_DUR, duration)?;
+        self.write_register(Registers::FRFL_THS, threshold)?;
+        self.write_register(Registers::FRFL_HYS, hysteresis)?;
+        Ok(())
+    }
+
+    /// Read the freefall detection settings from the sensor
+    pub fn freefall_config(&mut self) -> Result<FreefallConfig, Error<E>> {
+        let duration = self.read_register(Registers::FRFL_DUR)?;
+        let threshold = self.read_register(Registers::FRFL_THS)?;
+        let hysteresis = self.read_register(Registers::FRFL_HYS)?;
+        Ok(FreefallConfig::from_registers(duration, threshold, hysteresis))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threshold_codes_round_trip() {
+        // 1 LSB = 3.91 mg (ACTIVE_THS at 2g range)
+        assert_eq!(mg_to_code::<()>(78, 2000, 512, u8::MAX).ok(), Some(20));
+        assert_eq!(code_to_mg(20, 2000, 512), 78);
+        for code in 0..=u8::MAX {
+            let mg = code_to_mg(code, 16000, 512);
+            assert_eq!(mg_to_code::<()>(mg, 16000, 512, u8::MAX).ok(), Some(code));
+        }
+    }
+
+    #[test]
+    fn threshold_above_max_is_rejected() {
+        assert_eq!(mg_to_code::<()>(1937, 2000, 32, Bitmasks::TAP_THS).ok(), Some(31));
+        assert!(mg_to_code::<()>(2000, 2000, 32, Bitmasks::TAP_THS).is_err());
+        assert!(mg_to_code::<()>(8000, 2000, 512, u8::MAX).is_err());
+    }
+
+    #[test]
+    fn active_power_on_values() {
+        let config = ActiveConfig::from_registers(0x00, 0x14, Range::_2g);
+        assert_eq!(config.threshold_mg, ActiveConfig::default().threshold_mg);
+        assert_eq!(config.duration_ms, ActiveConfig::default().duration_ms);
+        assert_eq!(ActiveConfig::default().active_ths::<()>(Range::_2g).ok(), Some(0x14));
+        assert_eq!(ActiveConfig::default().active_dur::<()>().ok(), Some(0x00));
+    }
+
+    #[test]
+    fn tap_power_on_values() {
+        let config = TapConfig::from_registers(0x04, 0x0A, Range::_2g);
+        assert_eq!(config.threshold_mg, TapConfig::default().threshold_mg);
+        assert_eq!(TapConfig::default().tap_ths::<()>(Range::_2g).ok(), Some(0x0A));
+        assert_eq!(TapConfig::default().tap_dur(), 0x04);
+    }
+
+    #[test]
+    fn orient_power_on_values() {
+        let config = OrientConfig::from_register(0x18);
+        assert_eq!(config.hysteresis_mg, OrientConfig::default().hysteresis_mg);
+        assert_eq!(OrientConfig::default().orient_cfg::<()>().ok(), Some(0x18));
+    }
+
+    #[test]
+    fn freefall_power_on_values() {
+        let config = FreefallConfig::from_registers(0x09, 0x30, 0x01);
+        let default = FreefallConfig::default();
+        assert_eq!(config.duration_ms, default.duration_ms);
+        assert_eq!(config.threshold_mg, default.threshold_mg);
+        assert_eq!(config.hysteresis_mg, default.hysteresis_mg);
+        assert_eq!(default.frfl_dur::<()>().ok(), Some(0x09));
+        assert_eq!(default.frfl_ths::<()>().ok(), Some(0x30));
+        assert_eq!(default.frfl_hys::<()>().ok(), Some(0x01));
+    }
+}
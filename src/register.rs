@@ -86,7 +86,10 @@ impl Registers {
 pub struct Bitmasks;
 #[allow(dead_code)]
 impl Bitmasks {
-    // === INTERRUPT_CFG (0x0B) ===
+    // === SOFT_RESET (0x00) ===
+    pub (crate) const SOFT_RESET: u8 = 0b0010_0100;
+
+    // === DATA_INT (0x0A) ===
     pub (crate) const NEW_DATA_INT: u8 = 0b0000_0001;
 
 
@@ -101,28 +104,261 @@ impl Bitmasks {
     // === MOTION_INT = (0x09) ===
     pub (crate) const ORIENT_INT: u8 = 0b0100_0000;
     pub (crate) const S_TAP_INT: u8 = 0b0010_0000;
-    pub (crate) const D_TAP_INT: u8 = 0b0010_0000;
+    pub (crate) const D_TAP_INT: u8 = 0b0001_0000;
     pub (crate) const ACTIVE_INT: u8 = 0b0000_0100;
     pub (crate) const FREEFALL_INT: u8 = 0b0000_0001;
 
+    // === TAP_ACTIVE = (0x0B) ===
+    pub (crate) const TAP_SIGN: u8 = 0b1000_0000;
+    pub (crate) const TAP_FIRST_X: u8 = 0b0100_0000;
+    pub (crate) const TAP_FIRST_Y: u8 = 0b0010_0000;
+    pub (crate) const TAP_FIRST_Z: u8 = 0b0001_0000;
+    pub (crate) const ACTIVE_SIGN: u8 = 0b0000_1000;
+    pub (crate) const ACTIVE_FIRST_X: u8 = 0b0000_0100;
+    pub (crate) const ACTIVE_FIRST_Y: u8 = 0b0000_0010;
+    pub (crate) const ACTIVE_FIRST_Z: u8 = 0b0000_0001;
+
+    // === ORIENTATION = (0x0C) ===
+    pub (crate) const ORIENT_Z: u8 = 0b0100_0000;
+    pub (crate) const ORIENT_XY: u8 = 0b0011_0000;
+
     // === RES_RANGE = (0x0F) ===
     pub (crate) const RESOLUTION: u8 = 0b0000_1100;
     pub (crate) const FS: u8 = 0b0000_0011;
 
     // === CFG_ODR (0x10) ===
+    pub (crate) const AXES_DISABLE: u8 = 0b1110_0000;
     pub (crate) const ODR_MASK: u8 = 0b0000_1111;
 
     // === PWR_BW (0x11) ===
     pub (crate) const BW_MASK: u8 = 0b0001_1110;
     pub (crate) const PWR_MASK: u8 = 0b1100_0000;
 
+    // === POLAR_SWP (0x12) ===
+    pub (crate) const X_POLARITY: u8 = 0b0000_1000;
+    pub (crate) const Y_POLARITY: u8 = 0b0000_0100;
+    pub (crate) const Z_POLARITY: u8 = 0b0000_0010;
+    pub (crate) const XY_SWAP: u8 = 0b0000_0001;
+    pub (crate) const AXES_POLARITY: u8 = 0b0000_1110;
+
     // === INT_SET_1 (0x17) ===
     pub (crate) const NEW_DATA_INT_EN: u8 = 0b0001_0000;
     pub (crate) const FREEFALL_INT_EN: u8 = 0b0000_1000;
 
+    // === INT_MAP0 (0x19) ===
+    pub(crate) const INT1_ORIENT: u8 = 0b0100_0000;
+    pub(crate) const INT1_S_TAP: u8 = 0b0010_0000;
+    pub(crate) const INT1_D_TAP: u8 = 0b0001_0000;
+    pub(crate) const INT1_ACTIVE: u8 = 0b0000_0100;
+    pub(crate) const INT1_FREEFALL: u8 = 0b0000_0001;
+
+    // === INT_MAP1 (0x1A) ===
+    pub(crate) const INT1_NEW_DATA: u8 = 0b0000_0001;
+
     // === INT_LATCH (0x21) ===
     pub(crate) const RESET_INT: u8 = 0b1000_0000;
-    pub(crate) const LATCH_INT: u8 = 0b0111_1111;
+    pub(crate) const LATCH_INT: u8 = 0b0000_1111;
+
+    // === FRFL_HYS (0x24) ===
+    pub(crate) const FREEFALL_MODE: u8 = 0b0000_0100;
+    pub(crate) const FREEFALL_HYS: u8 = 0b0000_0011;
+
+    // === ACTIVE_DUR (0x27) ===
+    pub(crate) const ACTIVE_DUR: u8 = 0b0000_0011;
+
+    // === TAP_DUR (0x2A) ===
+    pub(crate) const TAP_QUIET: u8 = 0b1000_0000;
+    pub(crate) const TAP_SHOCK: u8 = 0b0100_0000;
+    pub(crate) const TAP_DUR: u8 = 0b0000_0111;
+
+    // === TAP_THS (0x2B) ===
+    pub(crate) const TAP_THS: u8 = 0b0001_1111;
+
+    // === ORIENT_CFG (0x2C) ===
+    pub(crate) const ORIENT_HYST: u8 = 0b0111_0000;
+    pub(crate) const ORIENT_BLOCK: u8 = 0b0000_1100;
+    pub(crate) const ORIENT_MODE: u8 = 0b0000_0011;
 }
 
 
+
+/// Shadow copy of the writable registers (RES_RANGE to OFFSET_Z), so that the settings
+/// can be changed without reading the register first and written only when they change
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RegisterCache {
+    values: [u8; RegisterCache::LEN],
+}
+
+impl Default for RegisterCache {
+    fn default() -> Self {
+        RegisterCache {
+            values: [0; RegisterCache::LEN],
+        }
+    }
+}
+
+impl RegisterCache {
+    /// First register of the block read into the cache
+    pub(crate) const START: Registers = Registers::RES_RANGE;
+
+    /// Length of the block from RES_RANGE to OFFSET_Z, including the reserved registers in between
+    const LEN: usize = (Registers::OFFSET_Z as usize) - (Registers::RES_RANGE as usize) + 1;
+
+    /// Registers kept in the cache
+    pub(crate) const WRITABLE: [Registers; 22] = [
+        Registers::RES_RANGE,
+        Registers::CFG_ODR,
+        Registers::PWR_BW,
+        Registers::POLAR_SWP,
+        Registers::INT_SET0,
+        Registers::INT_SET1,
+        Registers::INT_MAP0,
+        Registers::INT_MAP1,
+        Registers::INT_CFG,
+        Registers::INT_LATCH,
+        Registers::FRFL_DUR,
+        Registers::FRFL_THS,
+        Registers::FRFL_HYS,
+        Registers::ACTIVE_DUR,
+        Registers::ACTIVE_THS,
+        Registers::TAP_DUR,
+        Registers::TAP_THS,
+        Registers::ORIENT_CFG,
+        Registers::Z_COMP,
+        Registers::OFFSET_X,
+        Registers::OFFSET_Y,
+        Registers::OFFSET_Z,
+    ];
+
+    /// Buffer for reading the whole block from the sensor at once
+    pub(crate) fn buffer_mut(&mut self) -> &mut [u8] {
+        &mut self.values
+    }
+
+    /// Cached value of the register, `None` if the register is not cached
+    pub(crate) fn get(&self, register: Registers) -> Option<u8> {
+        Self::index(register).map(|i| self.values[i])
+    }
+
+    /// Check if the register must be written to hold this value
+    /// (the cache shows it has another one, or the register is not cached)
+    pub(crate) fn needs_write(&self, register: Registers, value: u8) -> bool {
+        self.get(register) != Some(value)
+    }
+
+    /// Update the cached value of the register, if it's cached
+    pub(crate) fn set(&mut self, register: Registers, value: u8) {
+        if let Some(i) = Self::index(register) {
+            self.values[i] = value;
+        }
+    }
+
+    fn index(register: Registers) -> Option<usize> {
+        use Registers::*;
+        match register {
+            RES_RANGE | CFG_ODR | PWR_BW | POLAR_SWP | INT_SET0 | INT_SET1 | INT_MAP0 | INT_MAP1
+            | INT_CFG | INT_LATCH | FRFL_DUR | FRFL_THS | FRFL_HYS | ACTIVE_DUR | ACTIVE_THS
+            | TAP_DUR | TAP_THS | ORIENT_CFG | Z_COMP | OFFSET_X | OFFSET_Y | OFFSET_Z => {
+                Some((register.addr() - Self::START.addr()) as usize)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AccelConfig;
+    use crate::interface::Interface;
+    use crate::{Range, MSA301};
+
+    /// Register map of a sensor, logging the writes
+    struct FakeSensor {
+        registers: [u8; 0x40],
+        writes: [(u8, u8); 32],
+        count: usize,
+    }
+
+    impl FakeSensor {
+        /// Each register holds its own address, PART_ID holds the sensor's ID
+        fn new() -> Self {
+            let mut registers = [0; 0x40];
+            for (addr, value) in registers.iter_mut().enumerate() {
+                *value = addr as u8;
+            }
+            registers[Registers::PART_ID.addr() as usize] = 0x13;
+            FakeSensor { registers, writes: [(0, 0); 32], count: 0 }
+        }
+
+        fn written(&self) -> &[(u8, u8)] {
+            &self.writes[..self.count]
+        }
+    }
+
+    impl Interface for FakeSensor {
+        type Error = ();
+
+        fn write(&mut self, addr: u8, value: u8) -> Result<(), Self::Error> {
+            self.registers[addr as usize] = value;
+            self.writes[self.count] = (addr, value);
+            self.count += 1;
+            Ok(())
+        }
+
+        fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+            let start = addr as usize;
+            buffer.copy_from_slice(&self.registers[start..start + buffer.len()]);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn cache_covers_the_writable_block() {
+        assert_eq!(RegisterCache::LEN, 44);
+        let mut cache = RegisterCache::default();
+        for (i, value) in cache.buffer_mut().iter_mut().enumerate() {
+            *value = RegisterCache::START.addr() + i as u8;
+        }
+        for register in RegisterCache::WRITABLE {
+            assert_eq!(cache.get(register), Some(register.addr()));
+        }
+        assert_eq!(cache.get(Registers::PART_ID), None);
+        assert_eq!(cache.get(Registers::ORIENTATION), None);
+    }
+
+    #[test]
+    fn cache_set_and_needs_write() {
+        let mut cache = RegisterCache::default();
+        cache.set(Registers::TAP_THS, 0x0A);
+        assert_eq!(cache.get(Registers::TAP_THS), Some(0x0A));
+        assert!(!cache.needs_write(Registers::TAP_THS, 0x0A));
+        assert!(cache.needs_write(Registers::TAP_THS, 0x0B));
+        // registers that are not cached are always written
+        cache.set(Registers::SOFT_RESET, 0x24);
+        assert!(cache.needs_write(Registers::SOFT_RESET, 0x24));
+    }
+
+    #[test]
+    fn repeated_write_is_skipped() {
+        let mut msa = MSA301::with_interface(FakeSensor::new(), AccelConfig::default()).unwrap();
+        msa.interface.count = 0;
+        msa.set_range(Range::_2g).unwrap();
+        assert_eq!(msa.interface.written(), &[(Registers::RES_RANGE.addr(), 0b0000_0000)]);
+        msa.set_range(Range::_2g).unwrap();
+        assert_eq!(msa.interface.count, 1);
+    }
+
+    #[test]
+    fn flush_writes_only_the_writable_registers() {
+        let mut msa = MSA301::with_interface(FakeSensor::new(), AccelConfig::default()).unwrap();
+        msa.interface.count = 0;
+        msa.flush().unwrap();
+        let written = msa.interface.written();
+        assert_eq!(written.len(), RegisterCache::WRITABLE.len());
+        for (register, (addr, value)) in RegisterCache::WRITABLE.iter().zip(written) {
+            assert_eq!(*addr, register.addr());
+            assert_eq!(Some(*value), msa.registers.get(*register));
+        }
+    }
+}
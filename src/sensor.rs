@@ -2,63 +2,341 @@
 //!
 //! TO DO: 
 //! * check if all the functions are implemented
-//! * should the readings be returned as a struct?
 
 use super::*;
+#[cfg(feature = "float")]
+use core::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+use interrupt::{InterruptStatus, TapActiveStatus};
+use mode::ActiveMode;
+use motion::Orientation;
 
-impl<I2C, E> MSA301<I2C>
-where
-    I2C: Write<Error = E> + WriteRead<Error = E>,
-{
-    /// Read the device ID ("who am I"). Returns decimal value 19.
-    pub fn get_device_id(&mut self) -> Result<u8, Error<E>> {
-        let whoami = self.read_register(Registers::PART_ID)?;
-        Ok(whoami)
+/// Standard gravity in m/s^2
+#[cfg(feature = "float")]
+const STANDARD_GRAVITY: f32 = 9.80665;
+
+/// Acceleration measured on the X/Y/Z axes, in g
+///
+/// ```rust,ignore
+/// let accel = msa301.read_acceleration().unwrap();
+/// let (x, y, z) = accel.to_mps2();
+/// let tilt = accel[Axis::Z] / accel.magnitude();
+/// ```
+#[cfg(feature = "float")]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Acceleration {
+    /// X axis
+    pub x: f32,
+    /// Y axis
+    pub y: f32,
+    /// Z axis
+    pub z: f32,
+}
+
+#[cfg(feature = "float")]
+impl Acceleration {
+    /// Create a new acceleration value, in g
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Acceleration { x, y, z }
     }
-    
-    /// Read raw sensor values
-    fn read_accel_raw(&mut self) -> Result<([u8;6]), Error<E>> {
-        let mut data = [0_u8;6];
-        self.i2c.write_read(DEV_ADDR, &[Registers::XAXIS_L.addr()], &mut data)
-            .map_err(Error::I2C)
-            .and(Ok(data))
+
+    /// Return the acceleration in m/s^2 (using the standard gravity, 9.80665 m/s^2)
+    pub fn to_mps2(self) -> (f32, f32, f32) {
+        (self.x * STANDARD_GRAVITY, self.y * STANDARD_GRAVITY, self.z * STANDARD_GRAVITY)
+    }
+
+    /// Return the acceleration in mg
+    pub fn to_mg(self) -> (f32, f32, f32) {
+        (self.x * 1000.0, self.y * 1000.0, self.z * 1000.0)
+    }
+
+    /// Return the magnitude of the acceleration vector, in g
+    pub fn magnitude(self) -> f32 {
+        libm::sqrtf(self.magnitude_squared())
+    }
+
+    /// Return the squared magnitude of the acceleration vector, in g^2 (no square root needed)
+    pub fn magnitude_squared(self) -> f32 {
+        self.x * self.x + self.y * self.y + self.z * self.z
+    }
+}
+
+#[cfg(feature = "float")]
+impl From<(f32, f32, f32)> for Acceleration {
+    fn from((x, y, z): (f32, f32, f32)) -> Self {
+        Acceleration { x, y, z }
+    }
+}
+
+#[cfg(feature = "float")]
+impl From<Acceleration> for (f32, f32, f32) {
+    fn from(accel: Acceleration) -> Self {
+        (accel.x, accel.y, accel.z)
+    }
+}
+
+#[cfg(feature = "float")]
+impl Index<Axis> for Acceleration {
+    type Output = f32;
+
+    fn index(&self, axis: Axis) -> &f32 {
+        match axis {
+            Axis::X => &self.x,
+            Axis::Y => &self.y,
+            Axis::Z => &self.z,
         }
+    }
+}
 
-    /// Read the accelerometer data as a tuple, 
-    /// correctly scaled according to the selected range.
-    /// 
-    /// ```rust
-    /// let (x,y,z) = msa301.read_accel().unwrap();            
-    /// println!("x: {}, y: {}, z: {}\r\n", x,y, z);  
-    /// ```
-    /// 
-    pub fn read_accel(&mut self) -> Result<(f32, f32, f32), Error<E>> {
-        let raw_data = self.read_accel_raw()?;
+#[cfg(feature = "float")]
+impl IndexMut<Axis> for Acceleration {
+    fn index_mut(&mut self, axis: Axis) -> &mut f32 {
+        match axis {
+            Axis::X => &mut self.x,
+            Axis::Y => &mut self.y,
+            Axis::Z => &mut self.z,
+        }
+    }
+}
 
-        let scale = self.config.range.sensitivity();
+#[cfg(feature = "float")]
+impl Add for Acceleration {
+    type Output = Acceleration;
 
-        let mut raw_x = (raw_data[1] as i16) << 8 | (raw_data[0] as i16);
-        raw_x = raw_x >> 2;
-        let x = (raw_x as f32) / scale;
-        let mut raw_y = (raw_data[3] as i16) << 8 | (raw_data[2] as i16);
-        raw_y = raw_y >> 2;
-        let y = (raw_y as f32) / scale;
-        let mut raw_z = (raw_data[5] as i16) << 8 | (raw_data[4] as i16);
-        raw_z = raw_z >> 2;
-        let z = (raw_z as f32) / scale;
+    fn add(self, other: Acceleration) -> Acceleration {
+        Acceleration::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
 
-        Ok((x,y,z))
+#[cfg(feature = "float")]
+impl Sub for Acceleration {
+    type Output = Acceleration;
 
+    fn sub(self, other: Acceleration) -> Acceleration {
+        Acceleration::new(self.x - other.x, self.y - other.y, self.z - other.z)
     }
+}
 
+#[cfg(feature = "float")]
+impl Mul<f32> for Acceleration {
+    type Output = Acceleration;
 
-    // == ADD QUANTIZED OUTPUT
+    fn mul(self, factor: f32) -> Acceleration {
+        Acceleration::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
 
-    /*
-    
-    should this return [i8;4], or (i8,i8,i8), or maybe u32 with the single bytes representing x: i8, y: i8, z: i8, scale (2,4,8 or 16)
+#[cfg(feature = "float")]
+impl Div<f32> for Acceleration {
+    type Output = Acceleration;
+
+    fn div(self, divisor: f32) -> Acceleration {
+        Acceleration::new(self.x / divisor, self.y / divisor, self.z / divisor)
+    }
+}
+
+#[cfg(feature = "float")]
+impl Neg for Acceleration {
+    type Output = Acceleration;
+
+    fn neg(self) -> Acceleration {
+        Acceleration::new(-self.x, -self.y, -self.z)
+    }
+}
+
+#[cfg(feature = "float")]
+impl AddAssign for Acceleration {
+    fn add_assign(&mut self, other: Acceleration) {
+        *self = *self + other;
+    }
+}
+
+#[cfg(feature = "float")]
+impl SubAssign for Acceleration {
+    fn sub_assign(&mut self, other: Acceleration) {
+        *self = *self - other;
+    }
+}
+
+/// Measurement quantized to 8 bits per axis, with the range code, to be sent in 4 bytes
+/// (e.g. in a radio payload)
+///
+/// 1 LSB is 1/128 of the full scale range (15.6 mg at +/-2g, 125 mg at +/-16g).
+/// The values are rounded to the nearest LSB, so the error compared to the measurement
+/// is at most half LSB (full scale/256), except for the values above 127 LSB,
+/// which saturate with an error of at most 1 LSB (full scale/128).
+///
+/// ```rust,ignore
+/// let payload = msa301.read_accel_quantized().unwrap().to_bytes(); // [x, y, z, range]
+/// // on the receiving side
+/// let (x, y, z) = QuantizedSample::from_bytes(payload).unwrap().to_mg();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuantizedSample {
+    /// X axis
+    pub x: i8,
+    /// Y axis
+    pub y: i8,
+    /// Z axis
+    pub z: i8,
+    /// Full scale range of the measurement
+    pub range: Range,
+}
+
+impl QuantizedSample {
+    /// Quantize the contents of the X/Y/Z axes registers (at any resolution)
+    pub(crate) fn from_registers(data: [u8; 6], range: Range) -> Self {
+        // the measurements are left-justified, so the high byte is the 8-bit value
+        let quantize = |low: u8, high: u8| {
+            let value = i16::from_le_bytes([low, high]) as i32;
+            ((value + 0x80) >> 8).min(i8::MAX as i32) as i8
+        };
+        QuantizedSample {
+            x: quantize(data[0], data[1]),
+            y: quantize(data[2], data[3]),
+            z: quantize(data[4], data[5]),
+            range,
+        }
+    }
+
+    /// Encode as 4 bytes: X, Y, Z and the range code (0 = 2g, 1 = 4g, 2 = 8g, 3 = 16g)
+    pub fn to_bytes(self) -> [u8; 4] {
+        [self.x as u8, self.y as u8, self.z as u8, self.range.value()]
+    }
+
+    /// Decode from 4 bytes, returns `None` if the range code is not valid
+    pub fn from_bytes(bytes: [u8; 4]) -> Option<Self> {
+        if bytes[3] > Bitmasks::FS {
+            return None;
+        }
+        Some(QuantizedSample {
+            x: bytes[0] as i8,
+            y: bytes[1] as i8,
+            z: bytes[2] as i8,
+            range: Range::from(bytes[3]),
+        })
+    }
+
+    /// Encode as `u32`, X in the lowest byte and the range code in the highest byte
+    pub fn pack(self) -> u32 {
+        u32::from_le_bytes(self.to_bytes())
+    }
+
+    /// Decode from `u32`, returns `None` if the range code is not valid
+    pub fn unpack(packed: u32) -> Option<Self> {
+        Self::from_bytes(packed.to_le_bytes())
+    }
+
+    /// Return the acceleration in mg, rounded to the nearest mg
+    pub fn to_mg(self) -> (i32, i32, i32) {
+        let full_scale = self.range.full_scale_mg() as i32;
+        let to_mg = |value: i8| {
+            let scaled = value as i32 * full_scale;
+            if scaled < 0 { (scaled - 64) / 128 } else { (scaled + 64) / 128 }
+        };
+        (to_mg(self.x), to_mg(self.y), to_mg(self.z))
+    }
+
+    /// Return the acceleration in g
+    #[cfg(feature = "float")]
+    pub fn to_acceleration(self) -> Acceleration {
+        let scale = self.range.full_scale_mg() as f32 / 128_000.0;
+        Acceleration::new(self.x as f32 * scale, self.y as f32 * scale, self.z as f32 * scale)
+    }
+}
+
+/// Measurements and status read at once from the XAXIS_L to ORIENTATION registers
+///
+/// ```rust,ignore
+/// let snapshot = msa301.snapshot().unwrap();
+/// if snapshot.motion.single_tap {
+///     let (x, y, z) = snapshot.accel_mg();
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    /// Raw X/Y/Z values, aligned to the resolution
+    pub raw: (i16, i16, i16),
+    /// Motion interrupts status
+    pub motion: InterruptStatus,
+    /// New data available
+    pub new_data: bool,
+    /// Axis and sign of the first tap and active interrupt triggering
+    pub tap_active: TapActiveStatus,
+    /// Orientation
+    pub orientation: Orientation,
+    range: Range,
+    resolution: Res,
+}
+
+impl Snapshot {
+    /// Number of registers read, from XAXIS_L (0x02) to ORIENTATION (0x0C)
+    pub(crate) const LEN: usize = 11;
+
+    /// Decode the contents of the XAXIS_L to ORIENTATION registers
+    pub(crate) fn from_registers(data: [u8; Self::LEN], range: Range, resolution: Res) -> Self {
+        let mut accel = [0_u8; 6];
+        accel.copy_from_slice(&data[..6]);
+        // data[6] is the reserved register 0x08
+        Snapshot {
+            raw: raw_from_registers(accel, resolution),
+            motion: InterruptStatus::from_register(data[7]),
+            new_data: data[8] & Bitmasks::NEW_DATA_INT != 0,
+            tap_active: TapActiveStatus::from_register(data[9]),
+            orientation: Orientation::from_register(data[10]),
+            range,
+            resolution,
+        }
+    }
+
+    /// Return the acceleration in mg, using only integer math
+    pub fn accel_mg(&self) -> (i32, i32, i32) {
+        mg_from_raw(self.raw, self.range, self.resolution)
+    }
+
+    /// Return the acceleration in g
+    #[cfg(feature = "float")]
+    pub fn acceleration(&self) -> Acceleration {
+        accel_from_raw(self.raw, self.range, self.resolution).into()
+    }
+}
+
+impl<T, E, MODE> MSA301<T, MODE>
+where
+    T: Interface<Error = E>,
+{
+    /// Read the device ID ("who am I"). Returns decimal value 19.
+    pub fn get_device_id(&mut self) -> Result<u8, Error<E>> {
+        let whoami = self.read_register(Registers::PART_ID)?;
+        Ok(whoami)
+    }
     
-    */
+    /// Set the hardware offset compensation for X/Y/Z axes, in mg
+    /// (1 LSB = 3.9 mg, from -500 mg to 496 mg). The offsets are subtracted from the measurements by the sensor.
+    ///
+    /// ```rust,ignore
+    /// msa301.set_offset(12, -8, 40).unwrap();
+    /// ```
+    ///
+    pub fn set_offset(&mut self, x: i16, y: i16, z: i16) -> Result<(), Error<E>> {
+        let x = offset_mg_to_code(x)?;
+        let y = offset_mg_to_code(y)?;
+        let z = offset_mg_to_code(z)?;
+        self.write_register(Registers::OFFSET_X, x)?;
+        self.write_register(Registers::OFFSET_Y, y)?;
+        self.write_register(Registers::OFFSET_Z, z)?;
+        Ok(())
+    }
+
+    /// Read the hardware offset compensation for X/Y/Z axes, in mg
+    pub fn get_offset(&mut self) -> Result<(i16, i16, i16), Error<E>> {
+        let x = self.read_register(Registers::OFFSET_X)?;
+        let y = self.read_register(Registers::OFFSET_Y)?;
+        let z = self.read_register(Registers::OFFSET_Z)?;
+        Ok((offset_code_to_mg(x), offset_code_to_mg(y), offset_code_to_mg(z)))
+    }
 
     /*
 
@@ -117,3 +395,381 @@ where
    
 
 }
+
+impl<T, E, MODE> MSA301<T, MODE>
+where
+    T: Interface<Error = E>,
+    MODE: ActiveMode,
+{
+    /// Read the contents of the X/Y/Z axes registers
+    fn read_accel_registers(&mut self) -> Result<[u8;6], Error<E>> {
+        let mut data = [0_u8;6];
+        self.interface.read(Registers::XAXIS_L.addr(), &mut data)
+            .map_err(Error::I2C)
+            .and(Ok(data))
+        }
+
+    /// Read the raw sensor values as a tuple, aligned to the selected resolution
+    /// (e.g. from -8192 to 8191 at 14 bits, from -128 to 127 at 8 bits)
+    /// 
+    /// ```rust,ignore
+    /// let (x,y,z) = msa301.read_accel_raw().unwrap();
+    /// ```
+    /// 
+    pub fn read_accel_raw(&mut self) -> Result<(i16, i16, i16), Error<E>> {
+        let data = self.read_accel_registers()?;
+        Ok(raw_from_registers(data, self.config.resolution))
+    }
+
+    /// Read the measurements and the motion, new data, tap/active and orientation status
+    /// in a single transfer, so that they are all from the same moment
+    /// 
+    /// ```rust,ignore
+    /// let snapshot = msa301.snapshot().unwrap();
+    /// println!("{:?} {:?}", snapshot.accel_mg(), snapshot.orientation);
+    /// ```
+    /// 
+    pub fn snapshot(&mut self) -> Result<Snapshot, Error<E>> {
+        let mut data = [0_u8; Snapshot::LEN];
+        self.interface.read(Registers::XAXIS_L.addr(), &mut data)
+            .map_err(Error::I2C)?;
+        Ok(Snapshot::from_registers(data, self.config.range, self.config.resolution))
+    }
+
+    /// Read the accelerometer data quantized to 8 bits per axis, with the range code
+    /// (see `QuantizedSample` for the error bounds)
+    /// 
+    /// ```rust,ignore
+    /// let packed: u32 = msa301.read_accel_quantized().unwrap().pack();
+    /// ```
+    /// 
+    pub fn read_accel_quantized(&mut self) -> Result<QuantizedSample, Error<E>> {
+        let data = self.read_accel_registers()?;
+        Ok(QuantizedSample::from_registers(data, self.config.range))
+    }
+
+    /// Read the accelerometer data in mg as a tuple, using only integer math
+    /// (rounded to the nearest mg, correctly scaled according to the selected range and resolution).
+    /// 
+    /// ```rust,ignore
+    /// let (x,y,z) = msa301.read_accel_mg().unwrap();
+    /// ```
+    /// 
+    pub fn read_accel_mg(&mut self) -> Result<(i32, i32, i32), Error<E>> {
+        let raw = self.read_accel_raw()?;
+        Ok(mg_from_raw(raw, self.config.range, self.config.resolution))
+    }
+
+    /// Read the accelerometer data as a tuple, 
+    /// correctly scaled according to the selected range and resolution.
+    /// 
+    /// ```rust,ignore
+    /// let (x,y,z) = msa301.read_accel().unwrap();            
+    /// println!("x: {}, y: {}, z: {}\r\n", x,y, z);  
+    /// ```
+    /// 
+    #[cfg(feature = "float")]
+    pub fn read_accel(&mut self) -> Result<(f32, f32, f32), Error<E>> {
+        let raw = self.read_accel_raw()?;
+        Ok(accel_from_raw(raw, self.config.range, self.config.resolution))
+    }
+
+    /// Read the accelerometer data as an `Acceleration` in g,
+    /// correctly scaled according to the selected range and resolution.
+    /// 
+    /// ```rust,ignore
+    /// let accel = msa301.read_acceleration().unwrap();
+    /// println!("{} g", accel.magnitude());
+    /// ```
+    /// 
+    #[cfg(feature = "float")]
+    pub fn read_acceleration(&mut self) -> Result<Acceleration, Error<E>> {
+        Ok(self.read_accel()?.into())
+    }
+}
+
+/// Convert the contents of the X/Y/Z axes registers into signed values aligned to the resolution
+/// (the measurements are left-justified in the 16 bits of each axis)
+pub(crate) fn raw_from_registers(data: [u8; 6], resolution: Res) -> (i16, i16, i16) {
+    let shift = 16 - resolution.bits();
+    let x = i16::from_le_bytes([data[0], data[1]]) >> shift;
+    let y = i16::from_le_bytes([data[2], data[3]]) >> shift;
+    let z = i16::from_le_bytes([data[4], data[5]]) >> shift;
+    (x, y, z)
+}
+
+/// Convert the raw values into acceleration in mg, rounded to the nearest mg
+/// (the full scale range corresponds to 2^(bits-1) LSB)
+pub(crate) fn mg_from_raw(raw: (i16, i16, i16), range: Range, resolution: Res) -> (i32, i32, i32) {
+    let full_scale = range.full_scale_mg() as i32;
+    let divisor = 1_i32 << (resolution.bits() - 1);
+    let to_mg = |value: i16| {
+        let scaled = value as i32 * full_scale;
+        if scaled < 0 { (scaled - divisor / 2) / divisor } else { (scaled + divisor / 2) / divisor }
+    };
+    let (x, y, z) = raw;
+    (to_mg(x), to_mg(y), to_mg(z))
+}
+
+/// Convert the raw values into acceleration in g,
+/// correctly scaled according to the selected range and resolution
+#[cfg(feature = "float")]
+pub(crate) fn accel_from_raw(raw: (i16, i16, i16), range: Range, resolution: Res) -> (f32, f32, f32) {
+    let scale = sensitivity(range, resolution);
+    let (x, y, z) = raw;
+    (x as f32 / scale, y as f32 / scale, z as f32 / scale)
+}
+
+/// Number of LSB per g for the selected range and resolution
+#[cfg(feature = "float")]
+pub(crate) fn sensitivity(range: Range, resolution: Res) -> f32 {
+    range.sensitivity() / (1 << (14 - resolution.bits())) as f32
+}
+
+/// Convert an offset in mg into the signed register value (1 LSB = 1000/256 mg), rounded to the nearest step
+pub(crate) fn offset_mg_to_code<E>(mg: i16) -> Result<u8, Error<E>> {
+    let scaled = mg as i32 * 256;
+    let code = if scaled < 0 { (scaled - 500) / 1000 } else { (scaled + 500) / 1000 };
+    if code < i8::MIN as i32 || code > i8::MAX as i32 {
+        Err(Error::InvalidInputData)
+    } else {
+        Ok(code as i8 as u8)
+    }
+}
+
+/// Convert the signed offset register value into mg, rounded to the nearest mg
+pub(crate) fn offset_code_to_mg(code: u8) -> i16 {
+    let scaled = code as i8 as i32 * 1000;
+    let mg = if scaled < 0 { (scaled - 128) / 256 } else { (scaled + 128) / 256 };
+    mg as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Left-justified register contents with the same value on the three axes
+    fn registers(value: u16) -> [u8; 6] {
+        let [low, high] = value.to_le_bytes();
+        [low, high, low, high, low, high]
+    }
+
+    #[test]
+    fn raw_full_scale_at_each_resolution() {
+        assert_eq!(raw_from_registers(registers(0x7FFC), Res::_14bit), (8191, 8191, 8191));
+        assert_eq!(raw_from_registers(registers(0x8000), Res::_14bit), (-8192, -8192, -8192));
+        assert_eq!(raw_from_registers(registers(0x7FF0), Res::_12bit), (2047, 2047, 2047));
+        assert_eq!(raw_from_registers(registers(0x8000), Res::_12bit), (-2048, -2048, -2048));
+        assert_eq!(raw_from_registers(registers(0x7FC0), Res::_10bit), (511, 511, 511));
+        assert_eq!(raw_from_registers(registers(0x8000), Res::_10bit), (-512, -512, -512));
+        assert_eq!(raw_from_registers(registers(0x7F00), Res::_8bit), (127, 127, 127));
+        assert_eq!(raw_from_registers(registers(0x8000), Res::_8bit), (-128, -128, -128));
+    }
+
+    #[test]
+    fn raw_keeps_the_sign_of_small_values() {
+        assert_eq!(raw_from_registers(registers(0xFFFC), Res::_14bit), (-1, -1, -1));
+        assert_eq!(raw_from_registers(registers(0xFF00), Res::_8bit), (-1, -1, -1));
+        // the unused low bits are dropped
+        assert_eq!(raw_from_registers(registers(0x0007), Res::_14bit), (1, 1, 1));
+    }
+
+    #[test]
+    fn raw_axes_order() {
+        let data = [0x04, 0x00, 0x08, 0x00, 0xFC, 0xFF];
+        assert_eq!(raw_from_registers(data, Res::_14bit), (1, 2, -1));
+    }
+
+    #[test]
+    fn mg_full_scale_at_each_resolution() {
+        assert_eq!(mg_from_raw((8191, -8192, 0), Range::_4g, Res::_14bit), (4000, -4000, 0));
+        assert_eq!(mg_from_raw((2047, -2048, 0), Range::_16g, Res::_12bit), (15992, -16000, 0));
+        assert_eq!(mg_from_raw((511, -512, 0), Range::_8g, Res::_10bit), (7984, -8000, 0));
+        assert_eq!(mg_from_raw((127, -128, 0), Range::_2g, Res::_8bit), (1984, -2000, 0));
+    }
+
+    #[test]
+    fn mg_rounds_half_away_from_zero() {
+        // 1 LSB = 15.625 mg
+        assert_eq!(mg_from_raw((1, -1, 0), Range::_2g, Res::_8bit), (16, -16, 0));
+        // 1 LSB = 0.244 mg
+        assert_eq!(mg_from_raw((1, -1, 3), Range::_2g, Res::_14bit), (0, 0, 1));
+        assert_eq!(mg_from_raw((-3, 2, -2), Range::_2g, Res::_14bit), (-1, 0, 0));
+        // 1 LSB = 0.977 mg
+        assert_eq!(mg_from_raw((1, -1, 0), Range::_2g, Res::_12bit), (1, -1, 0));
+    }
+
+    #[test]
+    fn offset_limits() {
+        assert_eq!(offset_mg_to_code::<()>(-500).ok(), Some(0x80));
+        assert_eq!(offset_mg_to_code::<()>(496).ok(), Some(0x7F));
+        assert!(offset_mg_to_code::<()>(-502).is_err());
+        assert!(offset_mg_to_code::<()>(499).is_err());
+        assert_eq!(offset_code_to_mg(0x80), -500);
+        assert_eq!(offset_code_to_mg(0x7F), 496);
+    }
+
+    #[test]
+    fn offset_rounds_to_the_nearest_step() {
+        // 1 LSB = 3.906 mg
+        assert_eq!(offset_mg_to_code::<()>(0).ok(), Some(0));
+        assert_eq!(offset_mg_to_code::<()>(2).ok(), Some(1));
+        assert_eq!(offset_mg_to_code::<()>(-2).ok(), Some(0xFF));
+        assert_eq!(offset_mg_to_code::<()>(1).ok(), Some(0));
+        assert_eq!(offset_code_to_mg(1), 4);
+        assert_eq!(offset_code_to_mg(0xFF), -4);
+    }
+
+    #[test]
+    fn quantized_rounds_and_saturates() {
+        let sample = |value: u16| QuantizedSample::from_registers(registers(value), Range::_4g).x;
+        assert_eq!(sample(0x7FFC), 127);
+        assert_eq!(sample(0x7F7F), 127);
+        assert_eq!(sample(0x8000), -128);
+        assert_eq!(sample(0x0080), 1);
+        assert_eq!(sample(0x007F), 0);
+        assert_eq!(sample(0xFF7F), -1);
+    }
+
+    #[test]
+    fn quantized_to_mg() {
+        let sample = QuantizedSample { x: 127, y: -128, z: 0, range: Range::_4g };
+        assert_eq!(sample.to_mg(), (3969, -4000, 0));
+        // 1 LSB = 15.625 mg
+        let sample = QuantizedSample { x: 1, y: -1, z: 64, range: Range::_2g };
+        assert_eq!(sample.to_mg(), (16, -16, 1000));
+    }
+
+    #[test]
+    fn quantized_bytes_round_trip() {
+        let sample = QuantizedSample { x: 1, y: -1, z: -128, range: Range::_16g };
+        assert_eq!(sample.to_bytes(), [0x01, 0xFF, 0x80, 0x03]);
+        assert_eq!(QuantizedSample::from_bytes(sample.to_bytes()), Some(sample));
+        assert_eq!(QuantizedSample::unpack(sample.pack()), Some(sample));
+    }
+
+    #[test]
+    fn quantized_invalid_range_byte() {
+        assert_eq!(QuantizedSample::from_bytes([0, 0, 0, 0x04]), None);
+        assert_eq!(QuantizedSample::from_bytes([0, 0, 0, 0xFF]), None);
+        assert_eq!(QuantizedSample::unpack(0x0400_0000), None);
+    }
+
+    #[test]
+    fn snapshot_decodes_every_register() {
+        let data = [
+            0x00, 0x20, // XAXIS: 2048 at 14 bits
+            0x00, 0xE0, // YAXIS: -2048
+            0x00, 0x00, // ZAXIS: 0
+            0xFF, // reserved register 0x08, ignored
+            0x51, // MOTION_INT: orientation, double tap and freefall
+            0x01, // DATA_INT: new data
+            0xA1, // TAP_ACTIVE: tap negative on Y, active positive on Z
+            0x60, // ORIENTATION: z downward, landscape left
+        ];
+        let snapshot = Snapshot::from_registers(data, Range::_4g, Res::_14bit);
+        assert_eq!(snapshot.raw, (2048, -2048, 0));
+        assert_eq!(snapshot.accel_mg(), (1000, -1000, 0));
+        assert!(snapshot.motion.orientation);
+        assert!(!snapshot.motion.single_tap);
+        assert!(snapshot.motion.double_tap);
+        assert!(!snapshot.motion.active);
+        assert!(snapshot.motion.freefall);
+        assert!(snapshot.new_data);
+        assert!(matches!(snapshot.tap_active.tap_sign, Sign::Negative));
+        assert!(matches!(snapshot.tap_active.tap_first_axis, Some(Axis::Y)));
+        assert!(matches!(snapshot.tap_active.active_sign, Sign::Positive));
+        assert!(matches!(snapshot.tap_active.active_first_axis, Some(Axis::Z)));
+        assert!(matches!(snapshot.orientation.xy, OrientXY::LandscapeLeft));
+        assert!(matches!(snapshot.orientation.z, OrientZ::Downward));
+    }
+
+    #[test]
+    fn snapshot_without_events() {
+        let snapshot = Snapshot::from_registers([0; Snapshot::LEN], Range::_2g, Res::_8bit);
+        assert_eq!(snapshot.raw, (0, 0, 0));
+        assert!(!snapshot.motion.orientation && !snapshot.motion.single_tap && !snapshot.motion.double_tap);
+        assert!(!snapshot.motion.active && !snapshot.motion.freefall);
+        assert!(!snapshot.new_data);
+        assert!(snapshot.tap_active.tap_first_axis.is_none());
+        assert!(snapshot.tap_active.active_first_axis.is_none());
+        assert!(matches!(snapshot.orientation.xy, OrientXY::PortraitUpright));
+        assert!(matches!(snapshot.orientation.z, OrientZ::Upward));
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod float_tests {
+    use super::*;
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn acceleration_units() {
+        let accel = Acceleration::new(1.0, -0.5, 0.25);
+        let (x, y, z) = accel.to_mps2();
+        assert!(approx(x, 9.80665) && approx(y, -4.903325) && approx(z, 2.4516625));
+        assert_eq!(accel.to_mg(), (1000.0, -500.0, 250.0));
+    }
+
+    #[test]
+    fn acceleration_magnitude() {
+        let accel = Acceleration::new(0.0, 3.0, -4.0);
+        assert_eq!(accel.magnitude_squared(), 25.0);
+        assert_eq!(accel.magnitude(), 5.0);
+        assert_eq!(Acceleration::default().magnitude(), 0.0);
+    }
+
+    #[test]
+    fn acceleration_index() {
+        let mut accel = Acceleration::new(1.0, 2.0, 3.0);
+        assert_eq!((accel[Axis::X], accel[Axis::Y], accel[Axis::Z]), (1.0, 2.0, 3.0));
+        accel[Axis::Y] = -2.0;
+        assert_eq!(accel, Acceleration::new(1.0, -2.0, 3.0));
+    }
+
+    #[test]
+    fn acceleration_operators() {
+        let a = Acceleration::new(1.0, 2.0, 3.0);
+        let b = Acceleration::new(0.5, -1.0, 0.25);
+        assert_eq!(a + b, Acceleration::new(1.5, 1.0, 3.25));
+        assert_eq!(a - b, Acceleration::new(0.5, 3.0, 2.75));
+        assert_eq!(a * 2.0, Acceleration::new(2.0, 4.0, 6.0));
+        assert_eq!(a / 2.0, Acceleration::new(0.5, 1.0, 1.5));
+        assert_eq!(-b, Acceleration::new(-0.5, 1.0, -0.25));
+        let mut c = a;
+        c += b;
+        assert_eq!(c, a + b);
+        c -= b;
+        assert_eq!(c, a);
+    }
+
+    #[test]
+    fn acceleration_tuple_conversions() {
+        let accel: Acceleration = (1.0, 0.5, -1.0).into();
+        assert_eq!(accel, Acceleration::new(1.0, 0.5, -1.0));
+        assert_eq!(<(f32, f32, f32)>::from(accel), (1.0, 0.5, -1.0));
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_round_trip() {
+        let data = [0x00, 0x20, 0x00, 0xE0, 0x00, 0x00, 0x00, 0x51, 0x01, 0xA1, 0x60];
+        let snapshot = Snapshot::from_registers(data, Range::_4g, Res::_14bit);
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(json.starts_with(r#"{"raw":[2048,-2048,0],"#));
+        assert!(json.ends_with(r#""range":"4g","resolution":"14bit"}"#));
+
+        let decoded: Snapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.raw, snapshot.raw);
+        assert_eq!(decoded.accel_mg(), snapshot.accel_mg());
+        assert_eq!(decoded.new_data, snapshot.new_data);
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+    }
+}